@@ -1,9 +1,9 @@
 use clap::{Parser, ValueEnum};
 use iced::{
-    executor, theme,
+    executor, keyboard, theme,
     widget::{
-        button, column, container, horizontal_space, image, pick_list, row, scrollable, text,
-        text_editor, text_input, Column, Container,
+        button, column, container, horizontal_space, image, mouse_area, pick_list, row,
+        scrollable, text, text_editor, text_input, Column, Container,
     },
     Application, Command, Element, Length, Settings, Subscription, Theme,
 };
@@ -12,12 +12,15 @@ use base64::Engine;
 use linkify::LinkFinder;
 use notify_rust::Notification;
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     fs,
     hash::{Hash, Hasher},
     path::{Path, PathBuf},
     process::Command as ProcessCommand,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
     time::{Duration, Instant},
 };
 
@@ -75,6 +78,8 @@ struct MessageRow {
     is_from_me: bool,
     reactions: Vec<Reaction>,
     attachments: Vec<Attachment>,
+    nonce: u128,
+    pending: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -91,11 +96,12 @@ enum PendingRequest {
     History,
     WatchSubscribe,
     WatchUnsubscribe,
-    Send,
+    Send(u128),
     ResolveContacts,
     ContactSearch,
     Reaction,
     AttachmentFetch,
+    OlderHistory(i64),
 }
 
 #[derive(Debug, Clone)]
@@ -104,12 +110,85 @@ struct AttachmentFetch {
     filename: String,
 }
 
+/// Longest edge a decoded attachment thumbnail is downscaled to before it's
+/// handed to iced as an `image::Handle`, keeping large photos from blowing up
+/// GPU texture memory or stalling the decode thread on huge originals.
+const THUMBNAIL_MAX_DIM: u32 = 320;
+
+struct ThumbnailResult {
+    key: String,
+    cache_path: Option<PathBuf>,
+}
+
+/// An action the user took while disconnected, queued to replay once a
+/// client reconnects. `id` is a `next_nonce()` value used to match the
+/// eventual RPC response back to this entry so it's only dequeued once
+/// the server actually confirms it.
+#[derive(Debug, Clone)]
+enum OutboxEntry {
+    SendChat {
+        id: u128,
+        chat_id: i64,
+        text: String,
+        attachments: Vec<String>,
+    },
+    SendTo {
+        id: u128,
+        to: String,
+        text: String,
+        attachments: Vec<String>,
+    },
+    Reaction {
+        id: u128,
+        guid: String,
+        reaction: String,
+    },
+}
+
+fn outbox_entry_id(entry: &OutboxEntry) -> u128 {
+    match entry {
+        OutboxEntry::SendChat { id, .. }
+        | OutboxEntry::SendTo { id, .. }
+        | OutboxEntry::Reaction { id, .. } => *id,
+    }
+}
+
 #[derive(Debug, Clone)]
 enum InputMode {
     None,
     Reaction,
+    Search,
+}
+
+#[derive(Debug, Clone)]
+enum PaletteEntry {
+    Chat { index: usize, label: String },
+    Contact { handle: String, label: String },
 }
 
+/// A fuzzy match against a loaded message in the currently selected chat,
+/// carrying the matched char indices into `label` so the search overlay can
+/// highlight them.
+#[derive(Debug, Clone)]
+struct MessageMatch {
+    index: usize,
+    label: String,
+    matched: Vec<usize>,
+}
+
+/// Accumulates incoming-message notifications for a single chat so a burst
+/// of messages collapses into one OS notification instead of one per
+/// message. Flushed once `NOTIFICATION_COALESCE_WINDOW` has elapsed since
+/// the first message in the burst.
+struct PendingNotification {
+    since: Instant,
+    count: u32,
+    sender: String,
+    last_body: String,
+}
+
+const NOTIFICATION_COALESCE_WINDOW: Duration = Duration::from_secs(2);
+
 #[derive(Debug, Clone)]
 enum AppMessage {
     Tick,
@@ -117,7 +196,9 @@ enum AppMessage {
     SelectChat(usize),
     SelectMessage(usize),
     LoadHistory,
+    MessagesScrolled(f32, f32),
     ToggleWatch,
+    ToggleMute,
     StartReaction,
     ComposeToChanged(String),
     ComposeAction(text_editor::Action),
@@ -128,6 +209,21 @@ enum AppMessage {
     SubmitReaction,
     CancelReaction,
     OpenUrl(String),
+    ShowFragmentMenu(Fragment),
+    CloseFragmentMenu,
+    CopyFragment(String),
+    StartChatWithHandle(String),
+    PickAttachment,
+    RemoveAttachment(usize),
+    ToggleSearch,
+    SearchQueryChanged(String),
+    JumpToMessage(usize),
+    SelectPaletteChat(usize),
+    SelectPaletteContact(String),
+    ComposeSuggestionMove(i32),
+    CommitComposeSuggestion,
+    DismissComposeSuggestions,
+    SelectComposeSuggestion(String),
 }
 
 struct App {
@@ -157,6 +253,28 @@ struct App {
     attachment_cache: HashMap<String, String>,
     pending_attachments: HashMap<String, AttachmentFetch>,
     attachment_dir: PathBuf,
+    outbox: Vec<OutboxEntry>,
+    outbox_inflight: HashMap<String, u128>,
+    oldest_loaded: HashMap<i64, (String, String)>,
+    loaded_all: HashMap<i64, bool>,
+    message_scroll_id: scrollable::Id,
+    scroll_restore_base: Option<f32>,
+    pending_scroll_restore: Option<f32>,
+    message_scroll_at_bottom: bool,
+    pending_scroll_to_bottom: bool,
+    fragment_menu: Option<Fragment>,
+    compose_attachments: Vec<PathBuf>,
+    search_query: String,
+    compose_suggestion_index: Option<usize>,
+    muted_chats: HashSet<i64>,
+    pending_notifications: HashMap<i64, PendingNotification>,
+    note_activate_tx: Sender<i64>,
+    note_activate_rx: Receiver<i64>,
+    thumbnail_cache: HashMap<String, image::Handle>,
+    pending_thumbnails: HashSet<String>,
+    failed_thumbnails: HashSet<String>,
+    thumbnail_tx: Sender<ThumbnailResult>,
+    thumbnail_rx: Receiver<ThumbnailResult>,
 }
 
 impl App {
@@ -178,6 +296,8 @@ impl App {
             },
         }
 
+        let (note_activate_tx, note_activate_rx) = mpsc::channel();
+        let (thumbnail_tx, thumbnail_rx) = mpsc::channel();
         let mut app = Self {
             client,
             pending: HashMap::new(),
@@ -205,6 +325,28 @@ impl App {
             attachment_cache: HashMap::new(),
             pending_attachments: HashMap::new(),
             attachment_dir: attachment_cache_dir(),
+            outbox: load_outbox(),
+            outbox_inflight: HashMap::new(),
+            oldest_loaded: HashMap::new(),
+            loaded_all: HashMap::new(),
+            message_scroll_id: scrollable::Id::unique(),
+            scroll_restore_base: None,
+            pending_scroll_restore: None,
+            message_scroll_at_bottom: true,
+            pending_scroll_to_bottom: false,
+            fragment_menu: None,
+            compose_attachments: Vec::new(),
+            search_query: String::new(),
+            compose_suggestion_index: None,
+            muted_chats: HashSet::new(),
+            pending_notifications: HashMap::new(),
+            note_activate_tx,
+            note_activate_rx,
+            thumbnail_cache: HashMap::new(),
+            pending_thumbnails: HashSet::new(),
+            failed_thumbnails: HashSet::new(),
+            thumbnail_tx,
+            thumbnail_rx,
         };
 
         app.request_chats();
@@ -230,6 +372,37 @@ impl App {
         }
     }
 
+    /// Fetch the page of messages just before the oldest one currently
+    /// loaded for `chat_id`. Returns whether a request was actually sent, so
+    /// callers can skip scheduling follow-up work (like a scroll restore)
+    /// when the chat is already fully loaded or a fetch is already pending.
+    fn request_older_history(&mut self, chat_id: i64) -> bool {
+        if self.loaded_all.get(&chat_id).copied().unwrap_or(false) {
+            return false;
+        }
+        if self
+            .pending
+            .values()
+            .any(|pending| matches!(pending, PendingRequest::OlderHistory(id) if *id == chat_id))
+        {
+            return false;
+        }
+        let Some(client) = &mut self.client else {
+            return false;
+        };
+        let mut params = serde_json::json!({ "chat_id": chat_id, "limit": 50 });
+        if let Some((guid, created_at)) = self.oldest_loaded.get(&chat_id) {
+            let before = if !guid.is_empty() { guid.clone() } else { created_at.clone() };
+            if let Some(obj) = params.as_object_mut() {
+                obj.insert("before".to_string(), Value::String(before));
+            }
+        }
+        let id = client.send_request("messages.history", Some(params));
+        self.pending.insert(id, PendingRequest::OlderHistory(chat_id));
+        self.status = format!("loading older history for chat {chat_id}");
+        true
+    }
+
     fn toggle_watch(&mut self, chat_id: i64) {
         if let Some(client) = &mut self.client {
             if let Some(sub) = self.watch_subscription.clone() {
@@ -252,25 +425,231 @@ impl App {
         }
     }
 
-    fn request_send_chat(&mut self, chat_id: i64, text: &str) {
+    fn toggle_mute(&mut self, chat_id: i64) {
+        if !self.muted_chats.remove(&chat_id) {
+            self.muted_chats.insert(chat_id);
+        }
+    }
+
+    /// Records an incoming message for `message.chat_id`'s coalescing
+    /// window; `flush_notifications` (run on every `Tick`) turns this into
+    /// an actual OS notification once the window elapses.
+    fn queue_notification(&mut self, message: &MessageRow) {
+        let sender = sender_display(&self.contacts, &message.sender);
+        let body = self.notification_body(message);
+        let entry = self
+            .pending_notifications
+            .entry(message.chat_id)
+            .or_insert_with(|| PendingNotification {
+                since: Instant::now(),
+                count: 0,
+                sender: sender.clone(),
+                last_body: String::new(),
+            });
+        entry.count += 1;
+        entry.sender = sender;
+        entry.last_body = body;
+    }
+
+    /// Builds the `guid -> (sender, text)` lookup `reply_preview` uses to
+    /// resolve reply previews.
+    fn message_lookup(&self) -> HashMap<String, (String, String)> {
+        let mut lookup = HashMap::new();
+        for message in &self.messages {
+            if !message.guid.is_empty() {
+                lookup.insert(message.guid.clone(), (message.sender.clone(), message.text.clone()));
+            }
+        }
+        lookup
+    }
+
+    /// Renders the body for a notification the same way the message bubble
+    /// would: tapbacks show `reaction_summary`, replies are prefixed with
+    /// `reply_preview`, so a reaction-only or reply-only message doesn't
+    /// surface as a blank notification.
+    fn notification_body(&self, message: &MessageRow) -> String {
+        if let Some(summary) = reaction_summary(&message.reactions) {
+            return summary;
+        }
+        let lookup = self.message_lookup();
+        if let Some(reply) = reply_preview(message, &lookup, &self.contacts) {
+            if message.text.is_empty() {
+                return reply;
+            }
+            return format!("{reply}\n{}", message.text);
+        }
+        message.text.clone()
+    }
+
+    fn flush_notifications(&mut self) {
+        let now = Instant::now();
+        let ready: Vec<i64> = self
+            .pending_notifications
+            .iter()
+            .filter(|(_, pending)| now.duration_since(pending.since) >= NOTIFICATION_COALESCE_WINDOW)
+            .map(|(chat_id, _)| *chat_id)
+            .collect();
+        for chat_id in ready {
+            if let Some(pending) = self.pending_notifications.remove(&chat_id) {
+                self.fire_notification(chat_id, pending);
+            }
+        }
+    }
+
+    /// Shows the OS notification for a flushed `PendingNotification`. On
+    /// Linux/XDG desktops that support notification actions, also spawns a
+    /// thread to wait for the click and report the chat id back through
+    /// `note_activate_tx` so `Tick` can dispatch `select_chat_by_id`; other
+    /// platforms (notify-rust's macOS/Windows backends) just show it.
+    fn fire_notification(&mut self, chat_id: i64, pending: PendingNotification) {
+        let body = if pending.count > 1 {
+            format!("{} ({} new messages)", truncate_notification_body(&pending.last_body), pending.count)
+        } else {
+            truncate_notification_body(&pending.last_body)
+        };
+        let mut notification = Notification::new();
+        notification.summary(&pending.sender).body(&body).appname("imsg");
+
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            notification.action("default", "Open");
+            match notification.show() {
+                Ok(handle) => {
+                    let tx = self.note_activate_tx.clone();
+                    thread::spawn(move || {
+                        handle.wait_for_action(|action| {
+                            if action == "default" {
+                                let _ = tx.send(chat_id);
+                            }
+                        });
+                    });
+                }
+                Err(_) => self.status = "notification failed".to_string(),
+            }
+        }
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        {
+            let _ = notification.show();
+        }
+    }
+
+    fn select_chat_index(&mut self, index: usize) {
+        let previous_chat_id = self.chats.get(self.selected).map(|chat| chat.id);
+        self.selected = index;
+        self.selected_message = None;
+        if let Some(chat) = self.chats.get(self.selected) {
+            if Some(chat.id) != previous_chat_id {
+                self.request_history(chat.id);
+                self.message_scroll_at_bottom = true;
+            }
+        }
+    }
+
+    fn select_chat_by_id(&mut self, chat_id: i64) {
+        if let Some(index) = self.chats.iter().position(|chat| chat.id == chat_id) {
+            self.select_chat_index(index);
+        }
+    }
+
+    fn request_send_chat(&mut self, chat_id: i64, text: &str, attachments: &[PathBuf]) -> Option<String> {
         if let Some(client) = &mut self.client {
-            let id = client.send_request(
-                "send",
-                Some(serde_json::json!({ "chat_id": chat_id, "text": text })),
-            );
-            self.pending.insert(id, PendingRequest::Send);
+            let nonce = next_nonce();
+            let mut params =
+                serde_json::json!({ "chat_id": chat_id, "text": text, "nonce": nonce.to_string() });
+            attach_payload(&mut params, attachments);
+            let id = client.send_request("send", Some(params));
+            self.pending.insert(id.clone(), PendingRequest::Send(nonce));
+            push_optimistic_message(&mut self.messages, chat_id, text, nonce);
             self.status = "sending...".to_string();
+            Some(id)
+        } else {
+            self.enqueue_outbox(OutboxEntry::SendChat {
+                id: next_nonce(),
+                chat_id,
+                text: text.to_string(),
+                attachments: attachment_paths_to_strings(attachments),
+            });
+            None
         }
     }
 
-    fn request_send_to(&mut self, to: &str, text: &str) {
+    fn request_send_to(&mut self, to: &str, text: &str, attachments: &[PathBuf]) -> Option<String> {
         if let Some(client) = &mut self.client {
-            let id = client.send_request(
-                "send",
-                Some(serde_json::json!({ "to": to, "text": text })),
-            );
-            self.pending.insert(id, PendingRequest::Send);
+            let mut params = serde_json::json!({ "to": to, "text": text });
+            attach_payload(&mut params, attachments);
+            let id = client.send_request("send", Some(params));
+            self.pending.insert(id.clone(), PendingRequest::Send(0));
             self.status = "sending...".to_string();
+            Some(id)
+        } else {
+            self.enqueue_outbox(OutboxEntry::SendTo {
+                id: next_nonce(),
+                to: to.to_string(),
+                text: text.to_string(),
+                attachments: attachment_paths_to_strings(attachments),
+            });
+            None
+        }
+    }
+
+    /// Queue an action the user took while disconnected so it survives a
+    /// restart and can be replayed once a client reconnects.
+    fn enqueue_outbox(&mut self, entry: OutboxEntry) {
+        self.outbox.push(entry);
+        save_outbox(&self.outbox);
+        self.status = "queued (offline)".to_string();
+    }
+
+    fn remove_outbox_entry(&mut self, entry_id: u128) {
+        self.outbox.retain(|entry| outbox_entry_id(entry) != entry_id);
+        save_outbox(&self.outbox);
+    }
+
+    /// Replay queued offline actions as real requests now that a client is
+    /// connected, tracking each dispatched request id so the entry is only
+    /// dequeued once its response confirms success.
+    fn flush_outbox(&mut self) {
+        if self.outbox.is_empty() {
+            return;
+        }
+        let entries = self.outbox.clone();
+        let count = entries.len();
+        for entry in entries {
+            let entry_id = outbox_entry_id(&entry);
+            let request_id = match &entry {
+                OutboxEntry::SendChat { chat_id, text, attachments, .. } => {
+                    let paths: Vec<PathBuf> = attachments.iter().map(PathBuf::from).collect();
+                    self.request_send_chat(*chat_id, text, &paths)
+                }
+                OutboxEntry::SendTo { to, text, attachments, .. } => {
+                    let paths: Vec<PathBuf> = attachments.iter().map(PathBuf::from).collect();
+                    self.request_send_to(to, text, &paths)
+                }
+                OutboxEntry::Reaction { guid, reaction, .. } => {
+                    self.request_reaction(guid, reaction)
+                }
+            };
+            if let Some(request_id) = request_id {
+                self.outbox_inflight.insert(request_id, entry_id);
+            }
+        }
+        self.status = format!("replaying {count} queued message(s)");
+    }
+
+    /// Drop the optimistic local-echo row carrying `nonce` (if still
+    /// pending) and restore its text into the compose editor, so a failed
+    /// send doesn't silently eat what the user typed.
+    fn rollback_optimistic_send(&mut self, nonce: u128) {
+        if nonce == 0 {
+            return;
+        }
+        if let Some(index) = self
+            .messages
+            .iter()
+            .position(|message| message.pending && message.nonce == nonce)
+        {
+            let removed = self.messages.remove(index);
+            self.compose_content = text_editor::Content::with_text(&removed.text);
         }
     }
 
@@ -285,14 +664,22 @@ impl App {
         }
     }
 
-    fn request_reaction(&mut self, guid: &str, reaction: &str) {
+    fn request_reaction(&mut self, guid: &str, reaction: &str) -> Option<String> {
         if let Some(client) = &mut self.client {
             let id = client.send_request(
                 "reactions.send",
                 Some(serde_json::json!({ "guid": guid, "reaction": reaction })),
             );
-            self.pending.insert(id, PendingRequest::Reaction);
+            self.pending.insert(id.clone(), PendingRequest::Reaction);
             self.status = "sending reaction...".to_string();
+            Some(id)
+        } else {
+            self.enqueue_outbox(OutboxEntry::Reaction {
+                id: next_nonce(),
+                guid: guid.to_string(),
+                reaction: reaction.to_string(),
+            });
+            None
         }
     }
 
@@ -327,6 +714,7 @@ impl App {
             return;
         }
         if !attachment.original_path.is_empty() && Path::new(&attachment.original_path).exists() {
+            self.request_thumbnail(key.clone(), Path::new(&attachment.original_path));
             self.attachment_cache
                 .insert(key, attachment.original_path.clone());
             return;
@@ -359,6 +747,24 @@ impl App {
         }
     }
 
+    /// Decodes and downscales an image attachment on a background thread so
+    /// a large photo never blocks the update loop; the result comes back
+    /// through `thumbnail_rx` and is drained on `Tick`, mirroring how
+    /// `note_activate_rx` carries notification clicks back into the app.
+    fn request_thumbnail(&mut self, key: String, path: &Path) {
+        if self.thumbnail_cache.contains_key(&key) || self.pending_thumbnails.contains(&key) {
+            return;
+        }
+        self.pending_thumbnails.insert(key.clone());
+        self.failed_thumbnails.remove(&key);
+        let tx = self.thumbnail_tx.clone();
+        let path = path.to_path_buf();
+        thread::spawn(move || {
+            let cache_path = decode_thumbnail(&path, THUMBNAIL_MAX_DIM);
+            let _ = tx.send(ThumbnailResult { key, cache_path });
+        });
+    }
+
     fn handle_rpc_event(&mut self, event: RpcEvent) {
         match event {
             RpcEvent::Response { id, result } => {
@@ -368,36 +774,52 @@ impl App {
             }
             RpcEvent::Error { id, error } => {
                 if let Some(req_id) = id {
-                    self.pending.remove(&req_id);
+                    if let Some(PendingRequest::Send(nonce)) = self.pending.remove(&req_id) {
+                        self.rollback_optimistic_send(nonce);
+                    }
+                    self.outbox_inflight.remove(&req_id);
                 }
                 self.status = format!("rpc error: {error}");
             }
             RpcEvent::Notification { method, params } => {
                 if method == "message" {
                     if let Some(message) = parse_notification_message(&params) {
-                        let should_append = self
-                            .chats
-                            .get(self.selected)
-                            .map(|chat| chat.id == message.chat_id)
-                            .unwrap_or(false);
-                        if should_append {
-                            self.messages.push(message.clone());
+                        let reconciled = message.nonce != 0
+                            && self
+                                .messages
+                                .iter()
+                                .position(|row| row.pending && row.nonce == message.nonce)
+                                .map(|index| self.messages[index] = message.clone())
+                                .is_some();
+                        if !reconciled {
+                            let should_append = self
+                                .chats
+                                .get(self.selected)
+                                .map(|chat| chat.id == message.chat_id)
+                                .unwrap_or(false);
+                            if should_append {
+                                self.messages.push(message.clone());
+                                if self.message_scroll_at_bottom {
+                                    self.pending_scroll_to_bottom = true;
+                                }
+                            }
                         }
                         if !self.contacts.contains_key(&message.sender) {
                             self.request_contact_resolve(&[message.sender.clone()]);
                         }
                         self.fetch_attachments_for_message(&message);
-                        if self.notify && !message.is_from_me {
-                            let sender = self
-                                .contacts
-                                .get(&message.sender)
-                                .cloned()
-                                .unwrap_or(message.sender.clone());
-                            let _ = Notification::new()
-                                .summary(&sender)
-                                .body(&message.text)
-                                .appname("imsg")
-                                .show();
+                        let is_focused = self
+                            .chats
+                            .get(self.selected)
+                            .map(|chat| chat.id == message.chat_id)
+                            .unwrap_or(false);
+                        if self.notify
+                            && !message.is_from_me
+                            && self.watch_chat_id == Some(message.chat_id)
+                            && !is_focused
+                            && !self.muted_chats.contains(&message.chat_id)
+                        {
+                            self.queue_notification(&message);
                         }
                         self.status = "new message".to_string();
                     }
@@ -405,6 +827,9 @@ impl App {
             }
             RpcEvent::Closed { message } => {
                 self.status = format!("rpc closed: {message}");
+                self.client = None;
+                self.pending.clear();
+                self.outbox_inflight.clear();
                 self.schedule_reconnect();
             }
         }
@@ -445,11 +870,24 @@ impl App {
                 }
             }
             PendingRequest::History => {
-                let messages = result
+                let messages: Vec<MessageRow> = result
                     .get("messages")
                     .and_then(|v| v.as_array())
                     .map(|list| list.iter().filter_map(parse_message).collect())
-                    .unwrap_or_else(Vec::new);
+                    .unwrap_or_default();
+                if let Some(chat) = self.chats.get(self.selected) {
+                    let chat_id = chat.id;
+                    self.loaded_all.insert(chat_id, messages.len() < 50);
+                    match messages.first() {
+                        Some(oldest) => {
+                            self.oldest_loaded
+                                .insert(chat_id, (oldest.guid.clone(), oldest.created_at.clone()));
+                        }
+                        None => {
+                            self.oldest_loaded.remove(&chat_id);
+                        }
+                    }
+                }
                 self.messages = messages;
                 self.selected_message = None;
                 self.status = "history loaded".to_string();
@@ -468,6 +906,49 @@ impl App {
                     self.request_contact_resolve(&handles);
                 }
             }
+            PendingRequest::OlderHistory(chat_id) => {
+                let rows: Vec<MessageRow> = result
+                    .get("messages")
+                    .and_then(|v| v.as_array())
+                    .map(|list| list.iter().filter_map(parse_message).collect())
+                    .unwrap_or_default();
+                if rows.len() < 50 {
+                    self.loaded_all.insert(chat_id, true);
+                }
+                if let Some(oldest) = rows.first() {
+                    self.oldest_loaded
+                        .insert(chat_id, (oldest.guid.clone(), oldest.created_at.clone()));
+                }
+                let is_current_chat = self
+                    .chats
+                    .get(self.selected)
+                    .map(|chat| chat.id == chat_id)
+                    .unwrap_or(false);
+                if is_current_chat && !rows.is_empty() {
+                    let handles: Vec<String> = rows
+                        .iter()
+                        .map(|m| m.sender.clone())
+                        .filter(|h| !h.is_empty())
+                        .filter(|h| !self.contacts.contains_key(h))
+                        .collect();
+                    for row in &rows {
+                        self.fetch_attachments_for_message(row);
+                    }
+                    if let Some(base) = self.scroll_restore_base.take() {
+                        self.pending_scroll_restore =
+                            Some(base + rows.len() as f32 * ESTIMATED_MESSAGE_ROW_HEIGHT);
+                    }
+                    let mut prepended = rows;
+                    prepended.extend(std::mem::take(&mut self.messages));
+                    self.messages = prepended;
+                    self.status = "older history loaded".to_string();
+                    if !handles.is_empty() {
+                        self.request_contact_resolve(&handles);
+                    }
+                } else {
+                    self.scroll_restore_base = None;
+                }
+            }
             PendingRequest::WatchSubscribe => {
                 if let Some(sub) = result.get("subscription") {
                     self.watch_subscription = Some(sub.to_string().trim_matches('"').to_string());
@@ -479,8 +960,11 @@ impl App {
                 self.watch_chat_id = None;
                 self.status = "watch unsubscribed".to_string();
             }
-            PendingRequest::Send => {
+            PendingRequest::Send(_nonce) => {
                 self.status = "sent".to_string();
+                if let Some(entry_id) = self.outbox_inflight.remove(request_id) {
+                    self.remove_outbox_entry(entry_id);
+                }
             }
             PendingRequest::ResolveContacts => {
                 let contacts = result
@@ -525,7 +1009,8 @@ impl App {
                     let body = self.compose_content.text().trim().to_string();
                     if !body.is_empty() && self.contact_query.is_some() {
                         let target = self.compose_to.clone();
-                        self.request_send_to(&target, &body);
+                        let attachments = std::mem::take(&mut self.compose_attachments);
+                        self.request_send_to(&target, &body, &attachments);
                         self.record_recipient(&target);
                         self.compose_content = text_editor::Content::new();
                         self.status = "sent".to_string();
@@ -541,6 +1026,9 @@ impl App {
             }
             PendingRequest::Reaction => {
                 self.status = "reaction sent".to_string();
+                if let Some(entry_id) = self.outbox_inflight.remove(request_id) {
+                    self.remove_outbox_entry(entry_id);
+                }
             }
             PendingRequest::AttachmentFetch => {
                 if let Some(entry) = self.pending_attachments.remove(request_id) {
@@ -553,6 +1041,7 @@ impl App {
                                 if fs::create_dir_all(&self.attachment_dir).is_ok()
                                     && fs::write(&path, decoded).is_ok()
                                 {
+                                    self.request_thumbnail(entry.key.clone(), &path);
                                     self.attachment_cache.insert(
                                         entry.key,
                                         path.to_string_lossy().to_string(),
@@ -560,6 +1049,7 @@ impl App {
                                 }
                             }
                         } else {
+                            self.request_thumbnail(entry.key.clone(), &path);
                             self.attachment_cache
                                 .insert(entry.key, path.to_string_lossy().to_string());
                         }
@@ -581,6 +1071,181 @@ impl App {
         }
     }
 
+    /// Rank every participant/identifier seen across `self.chats` that
+    /// matches `query` under the same fuzzy scorer as the command palette,
+    /// for the `to` field when starting a new conversation. Handles are
+    /// ordered by which chat they were most recently active in
+    /// (`last_message_at`), with the fuzzy score breaking ties. Recency comes
+    /// from chat activity, not local send history, so a recipient who hasn't
+    /// been messaged from this client yet still surfaces if they show up in a
+    /// recent chat; handles known only from `recipient_history` sort last.
+    fn rank_new_conversation_recipients(&self, query: &str) -> Vec<String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut most_recent: HashMap<String, String> = HashMap::new();
+        for chat in &self.chats {
+            let mut handles: Vec<&String> = chat.participants.iter().collect();
+            if !chat.identifier.is_empty() {
+                handles.push(&chat.identifier);
+            }
+            for handle in handles {
+                let entry = most_recent.entry(handle.clone()).or_default();
+                if chat.last_message_at.as_str() > entry.as_str() {
+                    entry.clone_from(&chat.last_message_at);
+                }
+            }
+        }
+        for handle in &self.recipient_history {
+            most_recent.entry(handle.clone()).or_default();
+        }
+        let mut scored: Vec<(i64, String, String)> = Vec::new();
+        for (handle, last_message_at) in &most_recent {
+            let name = self.contacts.get(handle).cloned().unwrap_or_default();
+            let joined = format!("{name} {handle}");
+            if let Some(score) = fuzzy_palette_score(query, &joined) {
+                scored.push((score, last_message_at.clone(), handle.clone()));
+            }
+        }
+        scored.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+        scored.into_iter().take(8).map(|entry| entry.2).collect()
+    }
+
+    fn contact_suggestions(&self) -> Vec<String> {
+        self.rank_new_conversation_recipients(&self.compose_to)
+    }
+
+    /// Detects an in-progress `@token` ending at the `compose_content`
+    /// cursor (e.g. the user just typed `@jo`), so the composer can offer
+    /// the same recipient suggestions inline in the message body as it does
+    /// for the `to` field.
+    fn compose_mention(&self) -> Option<String> {
+        let (line, column) = self.compose_content.cursor_position();
+        let line_text = self.compose_content.line(line)?;
+        let before_cursor: String = line_text.chars().take(column).collect();
+        let at_pos = before_cursor.rfind('@')?;
+        let token = &before_cursor[at_pos + '@'.len_utf8()..];
+        if token.is_empty() || token.chars().any(char::is_whitespace) {
+            return None;
+        }
+        Some(token.to_string())
+    }
+
+    /// Rank the currently selected chat's own participants against `query`,
+    /// for an in-body `@mention` — a mention only makes sense for someone
+    /// already in this conversation, unlike the `to` field's wider pool.
+    fn rank_chat_participants(&self, query: &str) -> Vec<String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let Some(chat) = self.chats.get(self.selected) else {
+            return Vec::new();
+        };
+        let mut scored: Vec<(i64, usize, String)> = Vec::new();
+        for handle in &chat.participants {
+            let name = self.contacts.get(handle).cloned().unwrap_or_default();
+            let joined = format!("{name} {handle}");
+            if let Some(score) = fuzzy_palette_score(query, &joined) {
+                scored.push((score, joined.len(), handle.clone()));
+            }
+        }
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        scored.into_iter().take(8).map(|entry| entry.2).collect()
+    }
+
+    fn mention_suggestions(&self) -> Vec<String> {
+        match self.compose_mention() {
+            Some(token) => self.rank_chat_participants(&token),
+            None => Vec::new(),
+        }
+    }
+
+    /// The suggestion list the keyboard/mouse are currently acting on: an
+    /// in-body `@mention` takes priority over the `to` field, since that's
+    /// what the user is actively typing.
+    fn active_compose_suggestions(&self) -> Vec<String> {
+        let mention = self.mention_suggestions();
+        if !mention.is_empty() {
+            mention
+        } else {
+            self.contact_suggestions()
+        }
+    }
+
+    /// Fuzzy-ranked matches for the command palette, searching both loaded
+    /// chats and known contacts/recipient history by `search_query`.
+    fn palette_results(&self) -> Vec<PaletteEntry> {
+        let query = self.search_query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut scored: Vec<(i64, usize, PaletteEntry)> = Vec::new();
+        for (index, chat) in self.chats.iter().enumerate() {
+            let label = if !chat.name.is_empty() {
+                chat.name.clone()
+            } else if !chat.identifier.is_empty() {
+                chat.identifier.clone()
+            } else {
+                "Unknown chat".to_string()
+            };
+            if let Some(score) = fuzzy_palette_score(query, &label) {
+                scored.push((score, label.len(), PaletteEntry::Chat { index, label }));
+            }
+        }
+        let mut seen_handles: HashSet<String> = HashSet::new();
+        for (handle, name) in &self.contacts {
+            let label = if name.is_empty() {
+                handle.clone()
+            } else {
+                format!("{name} <{handle}>")
+            };
+            if let Some(score) = fuzzy_palette_score(query, &label) {
+                scored.push((
+                    score,
+                    label.len(),
+                    PaletteEntry::Contact { handle: handle.clone(), label },
+                ));
+            }
+            seen_handles.insert(handle.clone());
+        }
+        for handle in &self.recipient_history {
+            if seen_handles.contains(handle) {
+                continue;
+            }
+            if let Some(score) = fuzzy_palette_score(query, handle) {
+                scored.push((
+                    score,
+                    handle.len(),
+                    PaletteEntry::Contact { handle: handle.clone(), label: handle.clone() },
+                ));
+            }
+        }
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        scored.into_iter().take(20).map(|entry| entry.2).collect()
+    }
+
+    /// Fuzzy-ranked matches for `search_query` against the currently selected
+    /// chat's loaded `text`/`sender`, so typing in the search overlay also
+    /// jumps to messages instead of only chats and contacts.
+    fn message_search_results(&self) -> Vec<MessageMatch> {
+        let query = self.search_query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut scored: Vec<(i64, MessageMatch)> = Vec::new();
+        for (index, message) in self.messages.iter().enumerate() {
+            let sender = sender_display(&self.contacts, &message.sender);
+            let label = format!("{sender}: {}", message.text);
+            if let Some((score, matched)) = fuzzy_palette_match(query, &label) {
+                scored.push((score, MessageMatch { index, label, matched }));
+            }
+        }
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.label.len().cmp(&b.1.label.len())));
+        scored.into_iter().take(20).map(|entry| entry.1).collect()
+    }
+
     fn record_recipient(&mut self, handle: &str) {
         let trimmed = handle.trim();
         if trimmed.is_empty() {
@@ -611,11 +1276,13 @@ impl App {
                 self.reconnect_attempts = 0;
                 self.watch_subscription = None;
                 self.pending.clear();
+                self.outbox_inflight.clear();
                 self.status = "reconnected".to_string();
                 self.request_chats();
                 if let Some(chat_id) = self.watch_chat_id {
                     self.request_watch_subscribe(chat_id);
                 }
+                self.flush_outbox();
             }
             Err(err) => {
                 self.status = format!("reconnect failed: {err}");
@@ -664,7 +1331,11 @@ impl Application for App {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        iced::time::every(Duration::from_millis(150)).map(|_| AppMessage::Tick)
+        Subscription::batch([
+            iced::time::every(Duration::from_millis(150)).map(|_| AppMessage::Tick),
+            keyboard::on_key_press(search_keybind),
+            keyboard::on_key_press(compose_suggestion_keybind),
+        ])
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
@@ -674,20 +1345,42 @@ impl Application for App {
                     self.last_tick = Instant::now();
                     self.drain_events();
                     self.handle_reconnect();
+                    self.flush_notifications();
+                    while let Ok(chat_id) = self.note_activate_rx.try_recv() {
+                        self.select_chat_by_id(chat_id);
+                    }
+                    while let Ok(result) = self.thumbnail_rx.try_recv() {
+                        self.pending_thumbnails.remove(&result.key);
+                        match result.cache_path {
+                            Some(path) => {
+                                self.thumbnail_cache
+                                    .insert(result.key, image::Handle::from_path(path));
+                            }
+                            None => {
+                                self.failed_thumbnails.insert(result.key);
+                            }
+                        }
+                    }
+                }
+                if let Some(offset) = self.pending_scroll_restore.take() {
+                    return scrollable::scroll_to(
+                        self.message_scroll_id.clone(),
+                        scrollable::AbsoluteOffset { x: 0.0, y: offset },
+                    );
+                }
+                if self.pending_scroll_to_bottom {
+                    self.pending_scroll_to_bottom = false;
+                    return scrollable::snap_to(
+                        self.message_scroll_id.clone(),
+                        scrollable::RelativeOffset { x: 0.0, y: 1.0 },
+                    );
                 }
             }
             AppMessage::RefreshChats => {
                 self.request_chats();
             }
             AppMessage::SelectChat(index) => {
-                let previous_chat_id = self.chats.get(self.selected).map(|chat| chat.id);
-                self.selected = index;
-                self.selected_message = None;
-                if let Some(chat) = self.chats.get(self.selected) {
-                    if Some(chat.id) != previous_chat_id {
-                        self.request_history(chat.id);
-                    }
-                }
+                self.select_chat_index(index);
             }
             AppMessage::SelectMessage(index) => {
                 self.selected_message = Some(index);
@@ -697,11 +1390,28 @@ impl Application for App {
                     self.request_history(chat.id);
                 }
             }
+            AppMessage::MessagesScrolled(offset_y, relative_y) => {
+                self.message_scroll_at_bottom = relative_y >= 0.98 || relative_y.is_nan();
+                if relative_y <= 0.02 {
+                    if let Some(chat) = self.chats.get(self.selected) {
+                        let chat_id = chat.id;
+                        if self.request_older_history(chat_id) {
+                            self.scroll_restore_base = Some(offset_y);
+                        }
+                    }
+                }
+            }
             AppMessage::ToggleWatch => {
                 if let Some(chat) = self.chats.get(self.selected) {
                     self.toggle_watch(chat.id);
                 }
             }
+            AppMessage::ToggleMute => {
+                if let Some(chat) = self.chats.get(self.selected) {
+                    let chat_id = chat.id;
+                    self.toggle_mute(chat_id);
+                }
+            }
             AppMessage::StartReaction => {
                 if let Some(index) = self.selected_message {
                     if let Some(message) = self.messages.get(index) {
@@ -720,9 +1430,11 @@ impl Application for App {
             }
             AppMessage::ComposeToChanged(value) => {
                 self.compose_to = value;
+                self.compose_suggestion_index = None;
             }
             AppMessage::ComposeAction(action) => {
                 self.compose_content.perform(action);
+                self.compose_suggestion_index = None;
             }
             AppMessage::SendCompose => {
                 let text = self.compose_content.text().trim().to_string();
@@ -733,29 +1445,55 @@ impl Application for App {
                 let target = self.compose_to.trim().to_string();
                 if target.is_empty() {
                     if let Some(chat) = self.chats.get(self.selected).cloned() {
-                        self.request_send_chat(chat.id, &text);
+                        let attachments = std::mem::take(&mut self.compose_attachments);
+                        self.request_send_chat(chat.id, &text, &attachments);
                         if !chat.identifier.is_empty() {
                             self.record_recipient(&chat.identifier);
                         }
                         self.compose_content = text_editor::Content::new();
-                        self.status = "sent".to_string();
                     } else {
                         self.status = "no chat selected".to_string();
                     }
                 } else if looks_like_handle(&target) {
-                    self.request_send_to(&target, &text);
+                    let attachments = std::mem::take(&mut self.compose_attachments);
+                    self.request_send_to(&target, &text, &attachments);
                     self.record_recipient(&target);
                     self.compose_content = text_editor::Content::new();
                     self.status = "sent".to_string();
                 } else {
-                    self.contact_query = Some(target.clone());
-                    self.status = "searching contacts...".to_string();
-                    self.request_contact_search(&target);
+                    let suggestions = self.contact_suggestions();
+                    if let [handle] = suggestions.as_slice() {
+                        let handle = handle.clone();
+                        let attachments = std::mem::take(&mut self.compose_attachments);
+                        self.request_send_to(&handle, &text, &attachments);
+                        self.record_recipient(&handle);
+                        self.compose_content = text_editor::Content::new();
+                        self.status = "sent".to_string();
+                    } else if suggestions.is_empty() {
+                        self.contact_query = Some(target.clone());
+                        self.status = "searching contacts...".to_string();
+                        self.request_contact_search(&target);
+                    } else {
+                        let labels: Vec<String> = suggestions
+                            .iter()
+                            .map(|handle| {
+                                let name = self.contacts.get(handle).cloned().unwrap_or_default();
+                                if name.is_empty() {
+                                    handle.clone()
+                                } else {
+                                    format!("{name} <{handle}>")
+                                }
+                            })
+                            .collect();
+                        self.status = format!("multiple matches: {}", labels.join(", "));
+                    }
                 }
             }
             AppMessage::ClearCompose => {
                 self.compose_to.clear();
                 self.compose_content = text_editor::Content::new();
+                self.compose_attachments.clear();
+                self.compose_suggestion_index = None;
                 self.status = "cleared".to_string();
             }
             AppMessage::ToggleHelp => {
@@ -791,6 +1529,103 @@ impl Application for App {
                     self.status = "failed to open url".to_string();
                 }
             }
+            AppMessage::ShowFragmentMenu(fragment) => {
+                self.fragment_menu = Some(fragment);
+            }
+            AppMessage::CloseFragmentMenu => {
+                self.fragment_menu = None;
+            }
+            AppMessage::CopyFragment(value) => {
+                self.fragment_menu = None;
+                self.status = "copied".to_string();
+                return iced::clipboard::write(value);
+            }
+            AppMessage::StartChatWithHandle(handle) => {
+                self.fragment_menu = None;
+                self.compose_to = handle;
+                self.status = "recipient prefilled".to_string();
+            }
+            AppMessage::PickAttachment => {
+                if let Some(paths) = rfd::FileDialog::new().pick_files() {
+                    self.compose_attachments.extend(paths);
+                    self.status = "attachment added".to_string();
+                }
+            }
+            AppMessage::RemoveAttachment(index) => {
+                if index < self.compose_attachments.len() {
+                    self.compose_attachments.remove(index);
+                }
+            }
+            AppMessage::ToggleSearch => {
+                self.input_mode = match self.input_mode {
+                    InputMode::Search => InputMode::None,
+                    _ => InputMode::Search,
+                };
+                self.search_query.clear();
+            }
+            AppMessage::SearchQueryChanged(value) => {
+                self.search_query = value;
+            }
+            AppMessage::SelectPaletteChat(index) => {
+                self.select_chat_index(index);
+                self.input_mode = InputMode::None;
+                self.search_query.clear();
+            }
+            AppMessage::SelectPaletteContact(handle) => {
+                self.compose_to = handle;
+                self.input_mode = InputMode::None;
+                self.search_query.clear();
+            }
+            AppMessage::JumpToMessage(index) => {
+                self.selected_message = Some(index);
+                self.pending_scroll_restore = Some(index as f32 * ESTIMATED_MESSAGE_ROW_HEIGHT);
+                self.input_mode = InputMode::None;
+                self.search_query.clear();
+            }
+            AppMessage::ComposeSuggestionMove(delta) => {
+                let suggestions = self.active_compose_suggestions();
+                if suggestions.is_empty() {
+                    self.compose_suggestion_index = None;
+                } else {
+                    let len = suggestions.len() as i32;
+                    let current = self.compose_suggestion_index.map_or(-1, |i| i as i32);
+                    let next = (current + delta).rem_euclid(len);
+                    self.compose_suggestion_index = Some(next as usize);
+                }
+            }
+            AppMessage::CommitComposeSuggestion => {
+                let suggestions = self.active_compose_suggestions();
+                let index = self.compose_suggestion_index.unwrap_or(0);
+                let Some(handle) = suggestions.get(index).cloned() else {
+                    return Command::none();
+                };
+                if let Some(token) = self.compose_mention() {
+                    for _ in 0..=token.chars().count() {
+                        self.compose_content
+                            .perform(text_editor::Action::Select(text_editor::Motion::Left));
+                    }
+                    self.compose_content
+                        .perform(text_editor::Action::Edit(text_editor::Edit::Backspace));
+                    self.compose_content.perform(text_editor::Action::Edit(
+                        text_editor::Edit::Paste(std::sync::Arc::new(format!("@{handle} "))),
+                    ));
+                } else {
+                    self.compose_to = handle;
+                }
+                self.compose_suggestion_index = None;
+            }
+            AppMessage::DismissComposeSuggestions => {
+                self.compose_suggestion_index = None;
+            }
+            AppMessage::SelectComposeSuggestion(handle) => {
+                let suggestions = self.active_compose_suggestions();
+                if let Some(index) = suggestions.iter().position(|entry| *entry == handle) {
+                    self.compose_suggestion_index = Some(index);
+                    return self.update(AppMessage::CommitComposeSuggestion);
+                }
+                self.compose_to = handle;
+                self.compose_suggestion_index = None;
+            }
         }
         Command::none()
     }
@@ -823,6 +1658,9 @@ impl Application for App {
                 text(format!("{connection} · {status_line}"))
                     .size(12)
                     .style(text_muted),
+                button(text("Search").size(12))
+                    .on_press(AppMessage::ToggleSearch)
+                    .style(theme::Button::Text),
                 button(text("Help").size(12))
                     .on_press(AppMessage::ToggleHelp)
                     .style(theme::Button::Text),
@@ -932,12 +1770,7 @@ impl Application for App {
             text_color: Some(text_primary),
         })));
 
-        let mut message_lookup: HashMap<String, (String, String)> = HashMap::new();
-        for message in &self.messages {
-            if !message.guid.is_empty() {
-                message_lookup.insert(message.guid.clone(), (message.sender.clone(), message.text.clone()));
-            }
-        }
+        let message_lookup = self.message_lookup();
 
         let mut message_items = Column::new().spacing(14);
         for (index, message) in self.messages.iter().enumerate() {
@@ -972,34 +1805,49 @@ impl Application for App {
             if let Some(reply) = reply_preview(message, &message_lookup, &self.contacts) {
                 bubble_contents = bubble_contents.push(text(reply).size(12).style(muted_color));
             }
-            bubble_contents = bubble_contents.push(text(message.text.clone()).size(16).style(text_color));
-            let urls = extract_urls(&message.text);
-            if !urls.is_empty() {
-                let mut link_row = row![].spacing(6);
-                for url in urls {
-                    let link = button(text(&url).size(12).style(imessage_blue))
-                        .on_press(AppMessage::OpenUrl(url.clone()))
-                        .style(theme::Button::Text);
-                    link_row = link_row.push(link);
+            if has_markdown_tokens(&message.text) {
+                let code_bg = iced::Color {
+                    r: background.r * 0.8,
+                    g: background.g * 0.8,
+                    b: background.b * 0.8,
+                    a: 1.0,
+                };
+                for block in parse_markdown_blocks(&message.text) {
+                    bubble_contents = bubble_contents.push(render_markdown_block(
+                        block,
+                        text_color,
+                        imessage_blue,
+                        code_bg,
+                    ));
+                }
+            } else {
+                let mut body_row = row![].spacing(0);
+                for fragment in parse_fragments(&message.text) {
+                    body_row = body_row.push(render_fragment(fragment, text_color, imessage_blue));
                 }
-                bubble_contents = bubble_contents.push(link_row);
+                bubble_contents = bubble_contents.push(body_row);
             }
+            let chip_bg = iced::Color {
+                r: background.r * 0.8,
+                g: background.g * 0.8,
+                b: background.b * 0.8,
+                a: 1.0,
+            };
             for attachment in &message.attachments {
-                if attachment_is_image(attachment) {
-                    if let Some(path) = cached_attachment_path(&self.attachment_cache, attachment) {
-                        let handle = image::Handle::from_path(path);
-                        bubble_contents = bubble_contents.push(
-                            image(handle)
-                                .width(Length::Fixed(200.0))
-                                .height(Length::Fixed(200.0)),
-                        );
+                let key = attachment_key(&attachment.original_path, &attachment.filename);
+                let show_image = attachment_is_image(attachment) && !self.failed_thumbnails.contains(&key);
+                if show_image {
+                    if let Some(handle) = self.thumbnail_cache.get(&key) {
+                        bubble_contents = bubble_contents.push(image(handle.clone()));
+                    } else if cached_attachment_path(&self.attachment_cache, attachment).is_some() {
+                        let label = format!("image: {} (decoding)", attachment.filename);
+                        bubble_contents = bubble_contents.push(text(label).size(12).style(muted_color));
                     } else {
                         let label = format!("image: {} (fetching)", attachment.filename);
-                        bubble_contents = bubble_contents.push(text(label).size(12).style(text_color));
+                        bubble_contents = bubble_contents.push(text(label).size(12).style(muted_color));
                     }
                 } else {
-                    let label = format!("attachment: {}", attachment.filename);
-                    bubble_contents = bubble_contents.push(text(label).size(12).style(text_color));
+                    bubble_contents = bubble_contents.push(attachment_chip(&attachment.filename, text_color, chip_bg));
                 }
             }
             if let Some(summary) = reaction_summary(&message.reactions) {
@@ -1040,9 +1888,20 @@ impl Application for App {
             })
             .unwrap_or_else(|| "Select a chat".to_string());
 
+        let mute_label = if self
+            .chats
+            .get(self.selected)
+            .map(|chat| self.muted_chats.contains(&chat.id))
+            .unwrap_or(false)
+        {
+            "Unmute"
+        } else {
+            "Mute"
+        };
         let actions = row![
             button(text("Reload").size(12)).on_press(AppMessage::LoadHistory),
             button(text("Watch").size(12)).on_press(AppMessage::ToggleWatch),
+            button(text(mute_label).size(12)).on_press(AppMessage::ToggleMute),
             button(text("React").size(12)).on_press(AppMessage::StartReaction),
         ]
         .spacing(8);
@@ -1075,13 +1934,23 @@ impl Application for App {
             })))
             .into()
         } else {
-            Container::new(scrollable(message_items).height(Length::Fill))
-                .padding(8)
-                .into()
+            Container::new(
+                scrollable(message_items)
+                    .height(Length::Fill)
+                    .id(self.message_scroll_id.clone())
+                    .on_scroll(|viewport| {
+                        let offset = viewport.absolute_offset();
+                        let relative = viewport.relative_offset();
+                        AppMessage::MessagesScrolled(offset.y, relative.y)
+                    }),
+            )
+            .padding(8)
+            .into()
         };
 
         let to_input = text_input("to (handle or name)", &self.compose_to)
             .on_input(AppMessage::ComposeToChanged)
+            .on_submit(AppMessage::CommitComposeSuggestion)
             .padding(8)
             .style(theme::TextInput::Custom(Box::new(CosmicInputStyle)));
         let recent_pick = pick_list(
@@ -1095,9 +1964,68 @@ impl Application for App {
             .height(Length::Fixed(120.0));
         let send = button(text("Send")).on_press(AppMessage::SendCompose);
         let clear = button(text("Clear")).on_press(AppMessage::ClearCompose);
-        let compose_row = row![to_input, recent_pick, send, clear].spacing(10);
+        let attach = button(text("Attach")).on_press(AppMessage::PickAttachment);
+        let compose_row = row![to_input, recent_pick, attach, send, clear].spacing(10);
+
+        let suggestions = self.active_compose_suggestions();
+        let mut suggestion_list = column![].spacing(2);
+        for (index, handle) in suggestions.iter().enumerate() {
+            let name = self.contacts.get(handle).cloned().unwrap_or_default();
+            let label = if name.is_empty() {
+                handle.clone()
+            } else {
+                format!("{name} <{handle}>")
+            };
+            let label_color = if self.compose_suggestion_index == Some(index) {
+                accent_soft
+            } else {
+                text_muted
+            };
+            suggestion_list = suggestion_list.push(
+                button(text(label).size(13).style(label_color))
+                    .on_press(AppMessage::SelectComposeSuggestion(handle.clone()))
+                    .style(theme::Button::Text)
+                    .width(Length::Fill),
+            );
+        }
+        let suggestion_dropdown = Container::new(suggestion_list).padding(6).style(
+            theme::Container::Custom(Box::new(CosmicContainerStyle {
+                background: surface,
+                text_color: Some(text_muted),
+            })),
+        );
+
+        let mut attachment_chips = row![].spacing(6);
+        for (index, path) in self.compose_attachments.iter().enumerate() {
+            let label = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string());
+            let chip = row![
+                text(label).size(12).style(text_muted),
+                button(text("x").size(12))
+                    .on_press(AppMessage::RemoveAttachment(index))
+                    .style(theme::Button::Text),
+            ]
+            .spacing(4);
+            attachment_chips = attachment_chips.push(Container::new(chip).padding(6).style(
+                theme::Container::Custom(Box::new(CosmicContainerStyle {
+                    background: surface,
+                    text_color: Some(text_muted),
+                })),
+            ));
+        }
 
-        let composer = Container::new(column![compose_row, editor].spacing(10))
+        let mut composer_column = column![compose_row].spacing(10);
+        if !suggestions.is_empty() {
+            composer_column = composer_column.push(suggestion_dropdown);
+        }
+        if !self.compose_attachments.is_empty() {
+            composer_column = composer_column.push(attachment_chips);
+        }
+        composer_column = composer_column.push(editor);
+
+        let composer = Container::new(composer_column)
             .padding(12)
             .style(theme::Container::Custom(Box::new(CosmicContainerStyle {
                 background: surface,
@@ -1116,6 +2044,87 @@ impl Application for App {
                 .into();
         }
 
+        if let Some(fragment) = &self.fragment_menu {
+            let value = fragment.as_str().to_string();
+            let mut actions = column![
+                text(value.clone()).size(14).style(text_primary),
+                button(text("Copy").size(14)).on_press(AppMessage::CopyFragment(value.clone())),
+            ]
+            .spacing(10);
+            if matches!(fragment, Fragment::Url(_)) {
+                actions = actions.push(
+                    button(text("Open").size(14)).on_press(AppMessage::OpenUrl(value.clone())),
+                );
+            }
+            if matches!(fragment, Fragment::Handle(_)) {
+                actions = actions.push(
+                    button(text("Start chat with this contact").size(14))
+                        .on_press(AppMessage::StartChatWithHandle(value.clone())),
+                );
+            }
+            actions = actions.push(button(text("Close").size(14)).on_press(AppMessage::CloseFragmentMenu));
+            return Container::new(actions)
+                .padding(24)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(theme::Container::Custom(Box::new(CosmicContainerStyle {
+                    background: app_bg,
+                    text_color: Some(text_primary),
+                })))
+                .into();
+        }
+
+        if matches!(self.input_mode, InputMode::Search) {
+            let mut results = column![].spacing(6);
+            for entry in self.palette_results() {
+                let (label, action) = match entry {
+                    PaletteEntry::Chat { index, label } => {
+                        (label, AppMessage::SelectPaletteChat(index))
+                    }
+                    PaletteEntry::Contact { handle, label } => {
+                        (label, AppMessage::SelectPaletteContact(handle))
+                    }
+                };
+                results = results.push(
+                    button(text(label).size(14))
+                        .on_press(action)
+                        .style(theme::Button::Text)
+                        .width(Length::Fill),
+                );
+            }
+            let message_matches = self.message_search_results();
+            if !message_matches.is_empty() {
+                results = results.push(text("Messages in this chat").size(12).style(text_primary));
+                for found in message_matches {
+                    results = results.push(
+                        button(highlighted_match_row(&found.label, &found.matched, text_primary, accent))
+                            .on_press(AppMessage::JumpToMessage(found.index))
+                            .style(theme::Button::Text)
+                            .width(Length::Fill),
+                    );
+                }
+            }
+            let overlay = column![
+                text("Jump to chat, contact, or message").size(18),
+                text_input("search chats, contacts, and messages", &self.search_query)
+                    .on_input(AppMessage::SearchQueryChanged)
+                    .padding(8)
+                    .style(theme::TextInput::Custom(Box::new(CosmicInputStyle))),
+                scrollable(results).height(Length::Fill),
+                button(text("Close")).on_press(AppMessage::ToggleSearch),
+            ]
+            .spacing(10);
+            return Container::new(overlay)
+                .padding(24)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(theme::Container::Custom(Box::new(CosmicContainerStyle {
+                    background: app_bg,
+                    text_color: Some(text_primary),
+                })))
+                .into();
+        }
+
         if matches!(self.input_mode, InputMode::Reaction) {
             let overlay = column![
                 text("Send reaction").size(18),
@@ -1178,11 +2187,130 @@ fn connect_from_config(config: &Flags) -> std::io::Result<RpcClient> {
     }
 }
 
+/// Rough estimate of a rendered message bubble's height in pixels, used to
+/// compensate the scroll offset after prepending older history so the view
+/// doesn't jump. Message bubbles vary in height, so this is approximate.
+const ESTIMATED_MESSAGE_ROW_HEIGHT: f32 = 96.0;
+
+fn search_keybind(key: keyboard::Key, modifiers: keyboard::Modifiers) -> Option<AppMessage> {
+    if modifiers.command() && key == keyboard::Key::Character("k".into()) {
+        Some(AppMessage::ToggleSearch)
+    } else {
+        None
+    }
+}
+
+/// Arrow/Tab/Escape handling for the recipient suggestion dropdown. Most
+/// text widgets consume these keys themselves while focused (iced's
+/// `text_input` explicitly ignores Up/Down/Tab so they reach here; its
+/// Escape and the `text_editor`'s Up/Down are captured for cursor movement
+/// and can't be intercepted this way, which is an acceptable gap since
+/// mouse selection on the dropdown always works).
+fn compose_suggestion_keybind(key: keyboard::Key, _modifiers: keyboard::Modifiers) -> Option<AppMessage> {
+    match key {
+        keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
+            Some(AppMessage::ComposeSuggestionMove(-1))
+        }
+        keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
+            Some(AppMessage::ComposeSuggestionMove(1))
+        }
+        keyboard::Key::Named(keyboard::key::Named::Tab) => Some(AppMessage::CommitComposeSuggestion),
+        keyboard::Key::Named(keyboard::key::Named::Escape) => {
+            Some(AppMessage::DismissComposeSuggestions)
+        }
+        _ => None,
+    }
+}
+
+/// Left-to-right subsequence match of `query` against `candidate`, fzf-style:
+/// a base score per matched char, a word-boundary bonus when the match
+/// follows a separator (or is the first char, or a camelCase boundary), and a
+/// consecutive-match bonus. Returns the matched char indices into `candidate`
+/// alongside the score so callers can highlight them; `None` if `query` isn't
+/// a subsequence of `candidate` at all.
+fn fuzzy_palette_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+    let raw: Vec<char> = candidate.chars().collect();
+    // Lowercase each `raw` char individually (rather than `candidate.to_lowercase()`
+    // as a whole) so `lower` stays index-aligned with `raw` even for characters
+    // whose full lowercasing expands to more than one char (e.g. 'İ').
+    let lower: Vec<char> = raw
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let mut qi = 0;
+    let mut score: i64 = 0;
+    let mut prev_matched = false;
+    let mut matched = Vec::new();
+    for (i, &c) in lower.iter().enumerate() {
+        if qi >= query.len() {
+            break;
+        }
+        if c == query[qi] {
+            score += 1;
+            let at_separator = i > 0 && matches!(raw[i - 1], ' ' | '.' | '@' | '+' | '-');
+            let at_camel_boundary = i > 0 && raw[i - 1].is_lowercase() && raw[i].is_uppercase();
+            if i == 0 || at_separator || at_camel_boundary {
+                score += 3;
+            }
+            if prev_matched {
+                score += 2;
+            }
+            prev_matched = true;
+            matched.push(i);
+            qi += 1;
+        } else {
+            prev_matched = false;
+        }
+    }
+    if qi == query.len() {
+        Some((score, matched))
+    } else {
+        None
+    }
+}
+
+fn fuzzy_palette_score(query: &str, candidate: &str) -> Option<i64> {
+    fuzzy_palette_match(query, candidate).map(|(score, _)| score)
+}
+
 fn reconnect_delay(attempt: u32) -> Duration {
     let seconds = 2_u64.saturating_mul(2_u64.saturating_pow(attempt.min(4)));
     Duration::from_secs(seconds.min(30))
 }
 
+/// Generate a process-unique nonce for an optimistically-echoed send, by
+/// combining wall-clock nanoseconds with a monotonic counter.
+fn next_nonce() -> u128 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    nanos.wrapping_add(counter as u128)
+}
+
+fn push_optimistic_message(messages: &mut Vec<MessageRow>, chat_id: i64, text: &str, nonce: u128) {
+    messages.push(MessageRow {
+        chat_id,
+        guid: String::new(),
+        reply_to_guid: None,
+        sender: String::new(),
+        text: text.to_string(),
+        created_at: "sending…".to_string(),
+        is_from_me: true,
+        reactions: Vec::new(),
+        attachments: Vec::new(),
+        nonce,
+        pending: true,
+    });
+}
+
 fn open_url(url: &str) -> std::io::Result<()> {
     #[cfg(target_os = "macos")]
     let mut cmd = ProcessCommand::new("open");
@@ -1197,12 +2325,23 @@ fn help_text() -> &'static str {
 Refresh: reload chats\n\
 History: load messages for selected chat\n\
 Watch: toggle streaming for selected chat\n\
+Mute: silence notifications for selected chat without stopping the stream\n\
 React: send a reaction to the selected message\n\
+Desktop notifications fire for watched, unfocused, unmuted chats and\n\
+coalesce rapid bursts; clicking one (where supported) jumps to that chat\n\
+Search: fuzzy-jump to a chat or contact, or a message in the selected\n\
+chat with matches highlighted (or Cmd/Ctrl+K)\n\
+Image attachments render as inline thumbnails once decoded; other files\n\
+show as a filename chip\n\
 Help: toggle this overlay\n\
 \n\
 Compose\n\
 To: leave empty to send to selected chat\n\
 Use Recent to pick previous recipients\n\
+Typing a name/handle in To shows a fuzzy suggestion dropdown ranked by\n\
+which chat the recipient was most recently active in; an @mention in the\n\
+message instead suggests the selected chat's own participants. Arrow keys\n\
+move the selection, Tab/Enter commits it, Escape dismisses it\n\
 Send: send compose message\n\
 Clear: reset compose fields\n"
 }
@@ -1233,6 +2372,107 @@ fn attachment_cache_dir() -> PathBuf {
     }
 }
 
+fn thumbnail_cache_dir() -> PathBuf {
+    if let Ok(home) = std::env::var("HOME") {
+        PathBuf::from(home).join(".cache/imsg/thumbnails")
+    } else {
+        std::env::temp_dir().join("imsg/thumbnails")
+    }
+}
+
+fn thumbnail_cache_path(digest: &str, max_dim: u32) -> PathBuf {
+    thumbnail_cache_dir().join(format!("{digest}_{max_dim}.png"))
+}
+
+fn outbox_file_path() -> PathBuf {
+    attachment_cache_dir()
+        .parent()
+        .map(|dir| dir.join("outbox.json"))
+        .unwrap_or_else(|| PathBuf::from("outbox.json"))
+}
+
+fn outbox_to_value(outbox: &[OutboxEntry]) -> Value {
+    let items: Vec<Value> = outbox
+        .iter()
+        .map(|entry| match entry {
+            OutboxEntry::SendChat { id, chat_id, text, attachments } => serde_json::json!({
+                "kind": "send_chat",
+                "id": id.to_string(),
+                "chat_id": chat_id,
+                "text": text,
+                "attachments": attachments,
+            }),
+            OutboxEntry::SendTo { id, to, text, attachments } => serde_json::json!({
+                "kind": "send_to",
+                "id": id.to_string(),
+                "to": to,
+                "text": text,
+                "attachments": attachments,
+            }),
+            OutboxEntry::Reaction { id, guid, reaction } => serde_json::json!({
+                "kind": "reaction",
+                "id": id.to_string(),
+                "guid": guid,
+                "reaction": reaction,
+            }),
+        })
+        .collect();
+    Value::Array(items)
+}
+
+fn parse_outbox_attachments(value: &Value) -> Vec<String> {
+    value
+        .get("attachments")
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+fn parse_outbox_entry(value: &Value) -> Option<OutboxEntry> {
+    let id: u128 = value.get("id")?.as_str()?.parse().ok()?;
+    match value.get("kind")?.as_str()? {
+        "send_chat" => Some(OutboxEntry::SendChat {
+            id,
+            chat_id: value.get("chat_id")?.as_i64()?,
+            text: value.get("text")?.as_str()?.to_string(),
+            attachments: parse_outbox_attachments(value),
+        }),
+        "send_to" => Some(OutboxEntry::SendTo {
+            id,
+            to: value.get("to")?.as_str()?.to_string(),
+            text: value.get("text")?.as_str()?.to_string(),
+            attachments: parse_outbox_attachments(value),
+        }),
+        "reaction" => Some(OutboxEntry::Reaction {
+            id,
+            guid: value.get("guid")?.as_str()?.to_string(),
+            reaction: value.get("reaction")?.as_str()?.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+fn load_outbox() -> Vec<OutboxEntry> {
+    let Ok(contents) = fs::read_to_string(outbox_file_path()) else {
+        return Vec::new();
+    };
+    let Ok(value) = serde_json::from_str::<Value>(&contents) else {
+        return Vec::new();
+    };
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(parse_outbox_entry).collect())
+        .unwrap_or_default()
+}
+
+fn save_outbox(outbox: &[OutboxEntry]) {
+    let path = outbox_file_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(path, outbox_to_value(outbox).to_string());
+}
+
 fn attachment_key(path: &str, filename: &str) -> String {
     let mut hasher = DefaultHasher::new();
     path.hash(&mut hasher);
@@ -1249,15 +2489,95 @@ fn attachment_ext(path: &str, filename: &str) -> String {
         .to_string()
 }
 
-fn attachment_is_image(attachment: &Attachment) -> bool {
-    if attachment.mime_type.starts_with("image/") {
-        return true;
+fn mime_for_ext(ext: &str) -> &'static str {
+    match ext.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "heic" => "image/heic",
+        "heif" => "image/heif",
+        "webp" => "image/webp",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        _ => "application/octet-stream",
     }
-    let ext = attachment_ext(&attachment.original_path, &attachment.filename);
-    matches!(
-        ext.as_str(),
-        "png" | "jpg" | "jpeg" | "gif" | "heic" | "heif" | "webp"
-    )
+}
+
+fn outgoing_attachment_payload(path: &Path) -> Value {
+    let path_str = path.to_string_lossy().to_string();
+    let filename = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path_str.clone());
+    let mime = mime_for_ext(&attachment_ext(&path_str, &filename));
+    serde_json::json!({ "path": path_str, "filename": filename, "mime": mime })
+}
+
+fn attach_payload(params: &mut Value, attachments: &[PathBuf]) {
+    if attachments.is_empty() {
+        return;
+    }
+    let payload: Vec<Value> = attachments.iter().map(|p| outgoing_attachment_payload(p)).collect();
+    if let Some(obj) = params.as_object_mut() {
+        obj.insert("attachments".to_string(), Value::Array(payload));
+    }
+}
+
+fn attachment_paths_to_strings(attachments: &[PathBuf]) -> Vec<String> {
+    attachments
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+fn sha256_hex(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Decodes `path` with the `image` crate and downscales it to `max_dim` on
+/// the long edge, preserving aspect ratio, caching the result on disk keyed
+/// by a SHA-256 of the source bytes so the same photo (even re-sent under a
+/// different path) is only ever decoded once across scrolls and reconnects.
+/// Returns `None` for missing or unreadable files so the caller can fall
+/// back to a file chip.
+fn decode_thumbnail(path: &Path, max_dim: u32) -> Option<PathBuf> {
+    let digest = sha256_hex(path)?;
+    let cache_path = thumbnail_cache_path(&digest, max_dim);
+    if cache_path.exists() {
+        return Some(cache_path);
+    }
+    let original = ::image::open(path).ok()?;
+    let thumbnail = original.thumbnail(max_dim, max_dim).to_rgba8();
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent).ok()?;
+    }
+    thumbnail.save(&cache_path).ok()?;
+    Some(cache_path)
+}
+
+/// `mime_type` comes back empty from the RPC server for some attachments; in
+/// that case sniff it from the filename/path extension instead of trusting
+/// an empty string.
+fn attachment_mime(attachment: &Attachment) -> String {
+    if !attachment.mime_type.is_empty() {
+        return attachment.mime_type.clone();
+    }
+    mime_guess::from_path(&attachment.filename)
+        .first()
+        .or_else(|| mime_guess::from_path(&attachment.original_path).first())
+        .map(|mime| mime.essence_str().to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string())
+}
+
+fn attachment_is_image(attachment: &Attachment) -> bool {
+    attachment_mime(attachment).starts_with("image/")
 }
 
 fn cached_attachment_path(
@@ -1274,13 +2594,416 @@ fn cached_attachment_path(
     None
 }
 
-fn extract_urls(text: &str) -> Vec<String> {
+#[derive(Debug, Clone, PartialEq)]
+enum Fragment {
+    Text(String),
+    Url(String),
+    Handle(String),
+}
+
+impl Fragment {
+    fn as_str(&self) -> &str {
+        match self {
+            Fragment::Text(value) | Fragment::Url(value) | Fragment::Handle(value) => value,
+        }
+    }
+}
+
+fn classify_word(word: &str) -> Fragment {
     let mut finder = LinkFinder::new();
     finder.kinds(&[linkify::LinkKind::Url]);
-    finder
-        .links(text)
-        .map(|link| link.as_str().to_string())
-        .collect()
+    if finder.links(word).any(|link| link.as_str() == word) {
+        Fragment::Url(word.to_string())
+    } else if looks_like_handle(word) {
+        Fragment::Handle(word.to_string())
+    } else {
+        Fragment::Text(word.to_string())
+    }
+}
+
+/// Tokenizes `text` by whitespace runs, classifying each non-whitespace token
+/// and coalescing consecutive `Text` fragments (including whitespace, which is
+/// always kept as `Text`) so that joining `fragment.as_str()` for every
+/// fragment reproduces the original string byte-for-byte.
+fn parse_fragments(text: &str) -> Vec<Fragment> {
+    let mut fragments: Vec<Fragment> = Vec::new();
+    let mut push = |fragment: Fragment| {
+        if fragment.as_str().is_empty() {
+            return;
+        }
+        match (fragments.last_mut(), &fragment) {
+            (Some(Fragment::Text(prev)), Fragment::Text(next)) => prev.push_str(next),
+            _ => fragments.push(fragment),
+        }
+    };
+    for chunk in text.split_inclusive(char::is_whitespace) {
+        let word = chunk.trim_end_matches(char::is_whitespace);
+        let whitespace = &chunk[word.len()..];
+        if word.is_empty() {
+            push(Fragment::Text(chunk.to_string()));
+            continue;
+        }
+        push(classify_word(word));
+        if !whitespace.is_empty() {
+            push(Fragment::Text(whitespace.to_string()));
+        }
+    }
+    fragments
+}
+
+fn render_fragment<'a>(
+    fragment: Fragment,
+    text_color: iced::Color,
+    link_color: iced::Color,
+) -> Element<'a, AppMessage> {
+    match fragment {
+        Fragment::Text(value) => text(value).size(16).style(text_color).into(),
+        Fragment::Url(value) => mouse_area(
+            button(text(value.clone()).size(16).style(link_color))
+                .on_press(AppMessage::OpenUrl(value.clone()))
+                .style(theme::Button::Text),
+        )
+        .on_right_press(AppMessage::ShowFragmentMenu(Fragment::Url(value)))
+        .into(),
+        Fragment::Handle(value) => mouse_area(text(value.clone()).size(16).style(link_color))
+            .on_right_press(AppMessage::ShowFragmentMenu(Fragment::Handle(value)))
+            .into(),
+    }
+}
+
+/// Renders a non-image attachment as a filename chip with a file icon glyph,
+/// matching the chip styling already used for pending compose attachments.
+fn attachment_chip<'a>(
+    filename: &str,
+    text_color: iced::Color,
+    background: iced::Color,
+) -> Element<'a, AppMessage> {
+    let chip = row![
+        text("\u{1F4CE}").size(12).style(text_color),
+        text(filename.to_string()).size(12).style(text_color),
+    ]
+    .spacing(4);
+    Container::new(chip)
+        .padding(6)
+        .style(theme::Container::Custom(Box::new(CosmicContainerStyle {
+            background,
+            text_color: Some(text_color),
+        })))
+        .into()
+}
+
+/// Renders `label` as a row of single-char text elements, coloring the chars
+/// at `matched` indices with `highlight_color` so a search result shows which
+/// part of the message/sender matched the query.
+fn highlighted_match_row<'a>(
+    label: &str,
+    matched: &[usize],
+    base_color: iced::Color,
+    highlight_color: iced::Color,
+) -> Element<'a, AppMessage> {
+    let mut row_contents = row![].spacing(0);
+    for (i, c) in label.chars().enumerate() {
+        let color = if matched.contains(&i) { highlight_color } else { base_color };
+        row_contents = row_contents.push(text(c.to_string()).size(14).style(color));
+    }
+    row_contents.into()
+}
+
+/// Cheap pre-check so plain messages (the common case) skip straight to
+/// `parse_fragments`/`render_fragment` instead of paying for block/inline
+/// markdown parsing.
+fn has_markdown_tokens(text: &str) -> bool {
+    text.contains('`')
+        || text.contains('*')
+        || (text.contains('[') && text.contains("]("))
+        || text.lines().any(|line| line.trim_start().starts_with('>'))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum MarkdownBlock {
+    Paragraph(String),
+    Blockquote(String),
+    CodeBlock(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockMode {
+    Paragraph,
+    Blockquote,
+    Fence,
+}
+
+fn push_markdown_block(blocks: &mut Vec<MarkdownBlock>, current: &mut String, mode: BlockMode) {
+    if current.is_empty() {
+        return;
+    }
+    let finished = std::mem::take(current);
+    blocks.push(match mode {
+        BlockMode::Paragraph => MarkdownBlock::Paragraph(finished),
+        BlockMode::Blockquote => MarkdownBlock::Blockquote(finished),
+        BlockMode::Fence => MarkdownBlock::CodeBlock(finished),
+    });
+}
+
+/// Splits `text` into paragraph, `>`-quoted, and triple-backtick-fenced
+/// sections, so fenced code never gets inline emphasis/link parsing applied
+/// to it and quoted lines render distinctly from plain ones.
+fn parse_markdown_blocks(text: &str) -> Vec<MarkdownBlock> {
+    let mut blocks = Vec::new();
+    let mut current = String::new();
+    let mut mode = BlockMode::Paragraph;
+    for line in text.split('\n') {
+        if line.trim_start().starts_with("```") {
+            push_markdown_block(&mut blocks, &mut current, mode);
+            mode = if mode == BlockMode::Fence {
+                BlockMode::Paragraph
+            } else {
+                BlockMode::Fence
+            };
+            continue;
+        }
+        if mode == BlockMode::Fence {
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(line);
+            continue;
+        }
+        let is_quote = line.trim_start().starts_with('>');
+        let line_mode = if is_quote { BlockMode::Blockquote } else { BlockMode::Paragraph };
+        if line_mode != mode {
+            push_markdown_block(&mut blocks, &mut current, mode);
+            mode = line_mode;
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        if is_quote {
+            current.push_str(line.trim_start().trim_start_matches('>').trim_start());
+        } else {
+            current.push_str(line);
+        }
+    }
+    push_markdown_block(&mut blocks, &mut current, mode);
+    blocks
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InlineStyle {
+    Plain,
+    Bold,
+    Italic,
+    Code,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum InlineSpan {
+    Run(InlineStyle, String),
+    Link { label: String, href: String },
+}
+
+/// Greedily splits `text` on the first matching delimiter left to right:
+/// `` ` `` code, `**` bold, `*` italic, or a `[label](url)` link.
+/// Unterminated delimiters are left as plain text rather than eating the
+/// rest of the message.
+fn split_inline_styles(text: &str) -> Vec<InlineSpan> {
+    let mut spans: Vec<InlineSpan> = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(body) = rest.strip_prefix('`') {
+            if let Some(end) = body.find('`') {
+                flush_plain_span(&mut spans, &mut plain);
+                spans.push(InlineSpan::Run(InlineStyle::Code, body[..end].to_string()));
+                rest = &body[end + 1..];
+                continue;
+            }
+        } else if let Some(body) = rest.strip_prefix("**") {
+            if let Some(end) = body.find("**") {
+                flush_plain_span(&mut spans, &mut plain);
+                spans.push(InlineSpan::Run(InlineStyle::Bold, body[..end].to_string()));
+                rest = &body[end + 2..];
+                continue;
+            }
+        } else if let Some(body) = rest.strip_prefix('*') {
+            if let Some(end) = body.find('*') {
+                flush_plain_span(&mut spans, &mut plain);
+                spans.push(InlineSpan::Run(InlineStyle::Italic, body[..end].to_string()));
+                rest = &body[end + 1..];
+                continue;
+            }
+        } else if let Some(body) = rest.strip_prefix('[') {
+            if let Some(label_end) = body.find(']') {
+                let after_label = &body[label_end + 1..];
+                if let Some(url_body) = after_label.strip_prefix('(') {
+                    if let Some(url_end) = url_body.find(')') {
+                        flush_plain_span(&mut spans, &mut plain);
+                        spans.push(InlineSpan::Link {
+                            label: body[..label_end].to_string(),
+                            href: url_body[..url_end].to_string(),
+                        });
+                        rest = &url_body[url_end + 1..];
+                        continue;
+                    }
+                }
+            }
+        }
+        let mut chars = rest.chars();
+        if let Some(c) = chars.next() {
+            plain.push(c);
+        }
+        rest = chars.as_str();
+    }
+    flush_plain_span(&mut spans, &mut plain);
+    spans
+}
+
+fn flush_plain_span(spans: &mut Vec<InlineSpan>, plain: &mut String) {
+    if !plain.is_empty() {
+        spans.push(InlineSpan::Run(InlineStyle::Plain, std::mem::take(plain)));
+    }
+}
+
+fn inline_style_font(style: InlineStyle) -> iced::Font {
+    match style {
+        InlineStyle::Plain => iced::Font::DEFAULT,
+        InlineStyle::Bold => iced::Font {
+            weight: iced::font::Weight::Bold,
+            ..iced::Font::DEFAULT
+        },
+        InlineStyle::Italic => iced::Font {
+            style: iced::font::Style::Italic,
+            ..iced::Font::DEFAULT
+        },
+        InlineStyle::Code => iced::Font::MONOSPACE,
+    }
+}
+
+fn render_markdown_fragment<'a>(
+    style: InlineStyle,
+    fragment: Fragment,
+    text_color: iced::Color,
+    link_color: iced::Color,
+    code_bg: iced::Color,
+) -> Element<'a, AppMessage> {
+    let font = inline_style_font(style);
+    match fragment {
+        Fragment::Text(value) => {
+            let label = text(value).size(16).style(text_color).font(font);
+            if style == InlineStyle::Code {
+                Container::new(label)
+                    .padding([0, 4])
+                    .style(theme::Container::Custom(Box::new(CosmicContainerStyle {
+                        background: code_bg,
+                        text_color: Some(text_color),
+                    })))
+                    .into()
+            } else {
+                label.into()
+            }
+        }
+        Fragment::Url(value) => mouse_area(
+            button(text(value.clone()).size(16).style(link_color).font(font))
+                .on_press(AppMessage::OpenUrl(value.clone()))
+                .style(theme::Button::Text),
+        )
+        .on_right_press(AppMessage::ShowFragmentMenu(Fragment::Url(value)))
+        .into(),
+        Fragment::Handle(value) => {
+            mouse_area(text(value.clone()).size(16).style(link_color).font(font))
+                .on_right_press(AppMessage::ShowFragmentMenu(Fragment::Handle(value)))
+                .into()
+        }
+    }
+}
+
+/// Renders `text_value`'s inline spans (bold/italic/code/links, with
+/// URL/handle detection still running inside plain spans) into a row.
+fn render_inline_row<'a>(
+    text_value: &str,
+    text_color: iced::Color,
+    link_color: iced::Color,
+    code_bg: iced::Color,
+) -> Element<'a, AppMessage> {
+    let mut body_row = row![].spacing(0);
+    for span in split_inline_styles(text_value) {
+        match span {
+            InlineSpan::Link { label, href } => {
+                body_row = body_row.push(
+                    mouse_area(
+                        button(text(label).size(16).style(link_color))
+                            .on_press(AppMessage::OpenUrl(href.clone()))
+                            .style(theme::Button::Text),
+                    )
+                    .on_right_press(AppMessage::ShowFragmentMenu(Fragment::Url(href))),
+                );
+            }
+            InlineSpan::Run(style, span_text) if style == InlineStyle::Code => {
+                body_row = body_row.push(render_markdown_fragment(
+                    style,
+                    Fragment::Text(span_text),
+                    text_color,
+                    link_color,
+                    code_bg,
+                ));
+            }
+            InlineSpan::Run(style, span_text) => {
+                for fragment in parse_fragments(&span_text) {
+                    body_row =
+                        body_row.push(render_markdown_fragment(style, fragment, text_color, link_color, code_bg));
+                }
+            }
+        }
+    }
+    body_row.into()
+}
+
+/// Renders one `MarkdownBlock`: a paragraph or blockquote becomes a row of
+/// styled inline fragments (blockquotes get a shaded background), a fenced
+/// code block becomes a bordered, monospaced block with no inline parsing
+/// at all.
+fn render_markdown_block<'a>(
+    block: MarkdownBlock,
+    text_color: iced::Color,
+    link_color: iced::Color,
+    code_bg: iced::Color,
+) -> Element<'a, AppMessage> {
+    match block {
+        MarkdownBlock::Paragraph(text_value) => render_inline_row(&text_value, text_color, link_color, code_bg),
+        MarkdownBlock::Blockquote(text_value) => Container::new(row![
+            text("\u{258E}").size(16).style(text_color),
+            render_inline_row(&text_value, text_color, link_color, code_bg),
+        ])
+        .padding([2, 8])
+        .style(theme::Container::Custom(Box::new(CosmicContainerStyle {
+            background: code_bg,
+            text_color: Some(text_color),
+        })))
+        .into(),
+        MarkdownBlock::CodeBlock(code) => Container::new(
+            text(code)
+                .size(14)
+                .font(iced::Font::MONOSPACE)
+                .style(text_color),
+        )
+        .padding(8)
+        .width(Length::Fill)
+        .style(theme::Container::Custom(Box::new(BubbleStyle {
+            background: code_bg,
+            text_color: Some(text_color),
+            border_color: Some(text_color),
+        })))
+        .into(),
+    }
+}
+
+fn truncate_notification_body(text: &str) -> String {
+    let mut body = text.to_string();
+    if body.len() > 120 {
+        body.truncate(120);
+        body.push('…');
+    }
+    body
 }
 
 fn reply_preview(
@@ -1521,6 +3244,12 @@ fn parse_message(value: &Value) -> Option<MessageRow> {
                     .collect()
             })
             .unwrap_or_default(),
+        nonce: value
+            .get("nonce")
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0),
+        pending: false,
     })
 }
 
@@ -1594,6 +3323,24 @@ mod tests {
         assert_eq!(reconnect_delay(4).as_secs(), 30);
         assert_eq!(reconnect_delay(10).as_secs(), 30);
     }
+
+    #[test]
+    fn fuzzy_palette_match_scores_subsequence_with_boundary_bonus() {
+        let (score, matched) = fuzzy_palette_match("cmd", "Command Palette").unwrap();
+        assert_eq!(matched, vec![0, 2, 6]);
+        assert_eq!(score, 6);
+        assert!(fuzzy_palette_match("xyz", "Command Palette").is_none());
+    }
+
+    #[test]
+    fn fuzzy_palette_match_keeps_raw_and_lower_aligned_for_expanding_lowercase() {
+        // 'İ' (LATIN CAPITAL LETTER I WITH DOT ABOVE) lowercases to two chars
+        // ("i" + a combining dot above), so a naive `candidate.to_lowercase()`
+        // would desync from `raw` by one char at every later position.
+        let (score, matched) = fuzzy_palette_match("ist", "İstanbul").unwrap();
+        assert_eq!(matched, vec![0, 1, 2]);
+        assert_eq!(score, 10);
+    }
 }
 
 fn main() -> iced::Result {
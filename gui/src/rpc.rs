@@ -0,0 +1,186 @@
+use serde::Deserialize;
+use serde_json::{value::RawValue, Value};
+use std::{
+    io,
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+pub enum RpcEvent {
+    Response { id: String, result: Value },
+    Error { id: Option<String>, error: Value },
+    Notification { method: String, params: Value },
+    Closed { message: String },
+}
+
+pub struct RpcClient {
+    outgoing: Sender<String>,
+    receiver: Receiver<RpcEvent>,
+    _child: Option<Child>,
+}
+
+impl RpcClient {
+    pub fn connect_local(imsg_bin: &str, db_path: Option<&str>) -> io::Result<Self> {
+        let mut cmd = Command::new(imsg_bin);
+        cmd.arg("rpc");
+        if let Some(db) = db_path {
+            cmd.arg("--db").arg(db);
+        }
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = cmd.spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("failed to open stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("failed to open stdout"))?;
+        let (outgoing, receiver) = connect_with_io(stdout, stdin);
+        Ok(Self {
+            outgoing,
+            receiver,
+            _child: Some(child),
+        })
+    }
+
+    pub fn connect_tcp(host: &str, port: u16) -> io::Result<Self> {
+        let stream = TcpStream::connect((host, port))?;
+        let read_stream = stream.try_clone()?;
+        let (outgoing, receiver) = connect_with_io(read_stream, stream);
+        Ok(Self {
+            outgoing,
+            receiver,
+            _child: None,
+        })
+    }
+
+    /// Serialize the request and hand it to the writer thread's unbounded
+    /// queue, returning the request id immediately. This never touches the
+    /// socket itself, so a stalled transport can't block the iced update
+    /// loop or the 150ms tick subscription.
+    pub fn send_request(&mut self, method: &str, params: Option<Value>) -> String {
+        let id = next_id();
+        let mut payload = serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method });
+        if let Some(params) = params {
+            if let Some(obj) = payload.as_object_mut() {
+                obj.insert("params".to_string(), params);
+            }
+        }
+        let _ = self.outgoing.send(payload.to_string());
+        id
+    }
+
+    pub fn events(&self) -> &Receiver<RpcEvent> {
+        &self.receiver
+    }
+}
+
+fn connect_with_io<R, W>(reader: R, writer: W) -> (Sender<String>, Receiver<RpcEvent>)
+where
+    R: io::Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    let (event_tx, event_rx) = mpsc::channel::<RpcEvent>();
+    let (outgoing_tx, outgoing_rx) = mpsc::channel::<String>();
+    let writer_events = event_tx.clone();
+    thread::spawn(move || writer_thread(writer, outgoing_rx, writer_events));
+    thread::spawn(move || reader_thread(reader, event_tx));
+    (outgoing_tx, event_rx)
+}
+
+/// Owns the socket/pipe and drains the outgoing queue. On the first failed
+/// write (broken pipe, reset connection, etc.) the rest of the queue is
+/// dropped and a `Closed` event is reported, so the app sees the same
+/// "reconnecting" path it would on a dead read side instead of silently
+/// losing messages into a socket that's already gone.
+fn writer_thread(mut writer: impl Write, outgoing: Receiver<String>, events: Sender<RpcEvent>) {
+    for line in outgoing {
+        if writeln!(writer, "{line}").is_err() || writer.flush().is_err() {
+            let _ = events.send(RpcEvent::Closed {
+                message: "rpc write failed".to_string(),
+            });
+            return;
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawId {
+    Str(String),
+    Num(i64),
+}
+
+impl RawId {
+    fn into_key(self) -> String {
+        match self {
+            RawId::Str(s) => s,
+            RawId::Num(n) => n.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Frame<'a> {
+    id: Option<RawId>,
+    method: Option<&'a str>,
+    #[serde(borrow)]
+    params: Option<&'a RawValue>,
+    #[serde(borrow)]
+    result: Option<&'a RawValue>,
+    #[serde(borrow)]
+    error: Option<&'a RawValue>,
+}
+
+fn raw_to_value(raw: &RawValue) -> Value {
+    serde_json::from_str(raw.get()).unwrap_or(Value::Null)
+}
+
+fn reader_thread(reader: impl io::Read, event_tx: Sender<RpcEvent>) {
+    let buffered = BufReader::new(reader);
+    for line in buffered.lines().map_while(Result::ok) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let Ok(frame) = serde_json::from_str::<Frame>(trimmed) else {
+            continue;
+        };
+        if let (Some(method), Some(params)) = (frame.method, frame.params) {
+            let _ = event_tx.send(RpcEvent::Notification {
+                method: method.to_string(),
+                params: raw_to_value(params),
+            });
+            continue;
+        }
+        let Some(id) = frame.id.map(RawId::into_key) else {
+            continue;
+        };
+        if let Some(result) = frame.result {
+            let _ = event_tx.send(RpcEvent::Response {
+                id,
+                result: raw_to_value(result),
+            });
+        } else if let Some(error) = frame.error {
+            let _ = event_tx.send(RpcEvent::Error {
+                id: Some(id),
+                error: raw_to_value(error),
+            });
+        }
+    }
+    let _ = event_tx.send(RpcEvent::Closed {
+        message: "rpc stream closed".to_string(),
+    });
+}
+
+fn next_id() -> String {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    COUNTER.fetch_add(1, Ordering::Relaxed).to_string()
+}
@@ -18,6 +18,7 @@ use serde_json::Value;
 use std::{
     collections::HashMap,
     io::{self, Stdout},
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
@@ -37,14 +38,24 @@ struct Args {
     host: String,
     #[arg(long, default_value_t = 57999)]
     port: u16,
+    #[arg(long)]
+    ipc_path: Option<String>,
     #[arg(long, default_value_t = true)]
     notify: bool,
+    /// Your own name/handle, used to highlight and notify on self-mentions.
+    #[arg(long)]
+    mention_name: Option<String>,
+    /// Approximate token budget for thread summarization requests; the
+    /// oldest loaded messages are dropped first to fit.
+    #[arg(long, default_value_t = 4000)]
+    context_tokens: usize,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum Transport {
     Local,
     Tcp,
+    Ipc,
 }
 
 #[derive(Debug, Clone)]
@@ -54,6 +65,7 @@ struct RpcConfig {
     db: Option<String>,
     host: String,
     port: u16,
+    ipc_path: Option<String>,
 }
 
 impl RpcConfig {
@@ -61,6 +73,12 @@ impl RpcConfig {
         match self.transport {
             Transport::Local => RpcClient::connect_local(&self.imsg_bin, self.db.as_deref()),
             Transport::Tcp => RpcClient::connect_tcp(&self.host, self.port),
+            Transport::Ipc => {
+                let path = self.ipc_path.as_deref().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "--ipc-path is required for ipc transport")
+                })?;
+                RpcClient::connect_ipc(path)
+            }
         }
     }
 }
@@ -100,10 +118,11 @@ enum PendingRequest {
     History,
     WatchSubscribe,
     WatchUnsubscribe,
-    Send,
+    Send(Option<i64>),
     ResolveContacts,
     ContactSearch,
     Reaction,
+    Summarize,
 }
 
 #[derive(Debug)]
@@ -111,6 +130,64 @@ enum InputMode {
     Normal,
     Compose,
     Reaction,
+    Find,
+    Palette,
+    Notifications,
+}
+
+#[derive(Debug, Clone)]
+struct UnreadNotification {
+    chat_id: i64,
+    guid: String,
+    sender: String,
+    snippet: String,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Command {
+    Refresh,
+    ToggleWatch,
+    ComposeReply,
+    ComposeNew,
+    React,
+    OpenUrl,
+    Find,
+    Summarize,
+    Notifications,
+    Help,
+}
+
+struct CommandEntry {
+    command: Command,
+    name: &'static str,
+    binding: &'static str,
+}
+
+const COMMANDS: &[CommandEntry] = &[
+    CommandEntry { command: Command::Refresh, name: "Refresh chats", binding: "r" },
+    CommandEntry { command: Command::ToggleWatch, name: "Toggle watch on selected chat", binding: "w" },
+    CommandEntry { command: Command::ComposeReply, name: "Compose message to selected chat", binding: "s" },
+    CommandEntry { command: Command::ComposeNew, name: "New message to a recipient", binding: "n" },
+    CommandEntry { command: Command::React, name: "React to selected message", binding: "R" },
+    CommandEntry { command: Command::OpenUrl, name: "Open first URL in selected message", binding: "o" },
+    CommandEntry { command: Command::Find, name: "Find chats & messages", binding: "/" },
+    CommandEntry { command: Command::Summarize, name: "Summarize loaded thread", binding: "S" },
+    CommandEntry { command: Command::Notifications, name: "Open notification center", binding: "N" },
+    CommandEntry { command: Command::Help, name: "Toggle help", binding: "h" },
+];
+
+#[derive(Debug, Clone, Copy)]
+enum FindTarget {
+    Chat(usize),
+    Message(usize),
+}
+
+#[derive(Debug, Clone)]
+struct FindMatch {
+    target: FindTarget,
+    display: String,
+    matched: Vec<usize>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -149,14 +226,29 @@ struct App {
     compose_to: String,
     compose_body: String,
     compose_field: ComposeField,
+    compose_drafts: HashMap<i64, (String, String)>,
     recipient_history: Vec<String>,
     history_index: Option<usize>,
     contact_suggestions: Vec<(String, String)>,
     show_help: bool,
+    mention_name: Option<String>,
+    find_query: String,
+    find_results: Vec<FindMatch>,
+    find_selected: usize,
+    palette_query: String,
+    palette_results: Vec<usize>,
+    palette_selected: usize,
+    context_tokens: usize,
+    summary: Option<String>,
+    unread: HashMap<i64, usize>,
+    notification_log: Vec<UnreadNotification>,
+    notification_selected: usize,
+    pending_jump_guid: Option<String>,
 }
 
 impl App {
-    fn new(notify: bool, config: RpcConfig) -> Self {
+    fn new(notify: bool, config: RpcConfig, mention_name: Option<String>, context_tokens: usize) -> Self {
+        let (recipient_history, notification_log) = load_persisted_state();
         Self {
             chats: Vec::new(),
             messages: Vec::new(),
@@ -181,12 +273,132 @@ impl App {
             compose_to: String::new(),
             compose_body: String::new(),
             compose_field: ComposeField::To,
-            recipient_history: Vec::new(),
+            compose_drafts: HashMap::new(),
+            recipient_history,
             history_index: None,
             contact_suggestions: Vec::new(),
             show_help: false,
+            mention_name,
+            find_query: String::new(),
+            find_results: Vec::new(),
+            find_selected: 0,
+            palette_query: String::new(),
+            palette_results: Vec::new(),
+            palette_selected: 0,
+            context_tokens,
+            summary: None,
+            unread: HashMap::new(),
+            notification_log,
+            notification_selected: 0,
+            pending_jump_guid: None,
+        }
+    }
+}
+
+const MAX_NOTIFICATION_LOG: usize = 50;
+
+fn state_file_path() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".cache/imsg/tui_state.json"))
+}
+
+/// Load the persisted recipient history and unread-notification log from
+/// disk, tolerating a missing or malformed file (fresh install / corrupt
+/// write) by falling back to empty state.
+fn load_persisted_state() -> (Vec<String>, Vec<UnreadNotification>) {
+    let Some(path) = state_file_path() else {
+        return (Vec::new(), Vec::new());
+    };
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return (Vec::new(), Vec::new());
+    };
+    let Ok(value) = serde_json::from_str::<Value>(&contents) else {
+        return (Vec::new(), Vec::new());
+    };
+    let recipient_history = value
+        .get("recipient_history")
+        .and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let notification_log = value
+        .get("notification_log")
+        .and_then(|v| v.as_array())
+        .map(|list| list.iter().filter_map(parse_notification_entry).collect())
+        .unwrap_or_default();
+    (recipient_history, notification_log)
+}
+
+fn save_persisted_state(app: &App) {
+    let Some(path) = state_file_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        if std::fs::create_dir_all(parent).is_err() {
+            return;
         }
     }
+    let notification_log: Vec<Value> = app
+        .notification_log
+        .iter()
+        .map(|entry| {
+            serde_json::json!({
+                "chat_id": entry.chat_id,
+                "guid": entry.guid,
+                "sender": entry.sender,
+                "snippet": entry.snippet,
+                "created_at": entry.created_at,
+            })
+        })
+        .collect();
+    let value = serde_json::json!({
+        "recipient_history": app.recipient_history,
+        "notification_log": notification_log,
+    });
+    if let Ok(text) = serde_json::to_string_pretty(&value) {
+        let _ = std::fs::write(path, text);
+    }
+}
+
+fn record_unread_notification(app: &mut App, message: &Message) {
+    let snippet: String = message.text.chars().take(80).collect();
+    app.notification_log.insert(
+        0,
+        UnreadNotification {
+            chat_id: message.chat_id,
+            guid: message.guid.clone(),
+            sender: message.sender.clone(),
+            snippet,
+            created_at: message.created_at.clone(),
+        },
+    );
+    app.notification_log.truncate(MAX_NOTIFICATION_LOG);
+    save_persisted_state(app);
+}
+
+fn parse_notification_entry(value: &Value) -> Option<UnreadNotification> {
+    Some(UnreadNotification {
+        chat_id: value.get("chat_id")?.as_i64()?,
+        guid: value.get("guid").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+        sender: value
+            .get("sender")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        snippet: value
+            .get("snippet")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        created_at: value
+            .get("created_at")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+    })
 }
 
 fn main() -> io::Result<()> {
@@ -197,6 +409,7 @@ fn main() -> io::Result<()> {
         db: args.db,
         host: args.host,
         port: args.port,
+        ipc_path: args.ipc_path,
     };
 
     let mut client = config.connect()?;
@@ -207,7 +420,14 @@ fn main() -> io::Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run_app(&mut terminal, &mut client, args.notify, config);
+    let result = run_app(
+        &mut terminal,
+        &mut client,
+        args.notify,
+        config,
+        args.mention_name,
+        args.context_tokens,
+    );
 
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
@@ -221,8 +441,10 @@ fn run_app(
     client: &mut RpcClient,
     notify: bool,
     config: RpcConfig,
+    mention_name: Option<String>,
+    context_tokens: usize,
 ) -> io::Result<()> {
-    let mut app = App::new(notify, config);
+    let mut app = App::new(notify, config, mention_name, context_tokens);
     request_chats(client, &mut app);
 
     loop {
@@ -252,11 +474,17 @@ fn handle_key(client: &mut RpcClient, app: &mut App, key: KeyEvent) -> io::Resul
         InputMode::Normal => handle_normal_key(client, app, key),
         InputMode::Compose => handle_compose_key(client, app, key),
         InputMode::Reaction => handle_input_reaction(client, app, key),
+        InputMode::Find => handle_find_key(client, app, key),
+        InputMode::Palette => handle_palette_key(client, app, key),
+        InputMode::Notifications => handle_notifications_key(client, app, key),
     }
 }
 
 fn handle_normal_key(client: &mut RpcClient, app: &mut App, key: KeyEvent) -> io::Result<bool> {
     match key.code {
+        KeyCode::Esc if app.summary.is_some() => {
+            app.summary = None;
+        }
         KeyCode::Char('q') => return Ok(true),
         KeyCode::Tab => {
             app.focus = match app.focus {
@@ -278,29 +506,334 @@ fn handle_normal_key(client: &mut RpcClient, app: &mut App, key: KeyEvent) -> io
         KeyCode::Char('c') => begin_compose(app, ComposeField::Body),
         KeyCode::Char('o') => handle_open_url(app),
         KeyCode::Char('R') => handle_reaction(app),
+        KeyCode::Char('S') => handle_summarize(client, app),
+        KeyCode::Char('N') => begin_notifications(app),
         KeyCode::Char('h') | KeyCode::Char('?') => {
             app.show_help = !app.show_help;
         }
+        KeyCode::Char('/') => begin_find(app),
+        KeyCode::Char(':') => begin_palette(app),
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn begin_palette(app: &mut App) {
+    app.input_mode = InputMode::Palette;
+    app.palette_query.clear();
+    app.palette_selected = 0;
+    refresh_palette_results(app);
+    app.status = "palette: type to filter, Enter to run, Esc to cancel".to_string();
+}
+
+fn handle_palette_key(client: &mut RpcClient, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::Normal;
+            app.palette_query.clear();
+            app.palette_results.clear();
+            app.status = "cancelled".to_string();
+        }
+        KeyCode::Enter => {
+            if let Some(&index) = app.palette_results.get(app.palette_selected) {
+                let command = COMMANDS[index].command;
+                app.input_mode = InputMode::Normal;
+                app.palette_query.clear();
+                app.palette_results.clear();
+                execute_command(client, app, command);
+            } else {
+                app.status = "no match".to_string();
+            }
+        }
+        KeyCode::Up => {
+            if app.palette_selected > 0 {
+                app.palette_selected -= 1;
+            }
+        }
+        KeyCode::Down => {
+            if app.palette_selected + 1 < app.palette_results.len() {
+                app.palette_selected += 1;
+            }
+        }
+        KeyCode::Backspace => {
+            app.palette_query.pop();
+            refresh_palette_results(app);
+        }
+        KeyCode::Char(c) => {
+            app.palette_query.push(c);
+            refresh_palette_results(app);
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn refresh_palette_results(app: &mut App) {
+    let query = app.palette_query.trim();
+    if query.is_empty() {
+        app.palette_results = (0..COMMANDS.len()).collect();
+        app.palette_selected = 0;
+        return;
+    }
+    let mut scored: Vec<(i64, usize)> = COMMANDS
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| fuzzy_match(query, entry.name).map(|(score, _)| (score, index)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    app.palette_results = scored.into_iter().map(|(_, index)| index).collect();
+    app.palette_selected = 0;
+}
+
+fn execute_command(client: &mut RpcClient, app: &mut App, command: Command) {
+    match command {
+        Command::Refresh => request_chats(client, app),
+        Command::ToggleWatch => handle_watch(client, app),
+        Command::ComposeReply => begin_compose(app, ComposeField::Body),
+        Command::ComposeNew => begin_compose(app, ComposeField::To),
+        Command::React => handle_reaction(app),
+        Command::OpenUrl => handle_open_url(app),
+        Command::Find => begin_find(app),
+        Command::Summarize => handle_summarize(client, app),
+        Command::Notifications => begin_notifications(app),
+        Command::Help => app.show_help = !app.show_help,
+    }
+}
+
+fn begin_find(app: &mut App) {
+    app.input_mode = InputMode::Find;
+    app.find_query.clear();
+    app.find_selected = 0;
+    refresh_find_results(app);
+    app.status = "find: type to search, Enter to jump, Esc to cancel".to_string();
+}
+
+fn handle_find_key(client: &mut RpcClient, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::Normal;
+            app.find_query.clear();
+            app.find_results.clear();
+            app.status = "cancelled".to_string();
+        }
+        KeyCode::Enter => {
+            if let Some(found) = app.find_results.get(app.find_selected).cloned() {
+                apply_find_selection(client, app, &found);
+                app.input_mode = InputMode::Normal;
+                app.find_query.clear();
+                app.find_results.clear();
+            } else {
+                app.status = "no match".to_string();
+            }
+        }
+        KeyCode::Up => {
+            if app.find_selected > 0 {
+                app.find_selected -= 1;
+            }
+        }
+        KeyCode::Down => {
+            if app.find_selected + 1 < app.find_results.len() {
+                app.find_selected += 1;
+            }
+        }
+        KeyCode::Backspace => {
+            app.find_query.pop();
+            refresh_find_results(app);
+        }
+        KeyCode::Char(c) => {
+            app.find_query.push(c);
+            refresh_find_results(app);
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn apply_find_selection(client: &mut RpcClient, app: &mut App, found: &FindMatch) {
+    match found.target {
+        FindTarget::Chat(index) => {
+            if let Some(chat) = app.chats.get(index) {
+                let chat_id = chat.id;
+                switch_selected_chat(app, index);
+                app.focus = FocusPane::Chats;
+                request_history(client, app, chat_id);
+                app.message_offset = 0;
+                app.message_index = 0;
+            }
+        }
+        FindTarget::Message(index) => {
+            app.focus = FocusPane::Messages;
+            app.message_index = index;
+            app.message_offset = index;
+        }
+    }
+}
+
+fn begin_notifications(app: &mut App) {
+    app.input_mode = InputMode::Notifications;
+    app.notification_selected = 0;
+    app.status = "notifications: Enter jump, Esc cancel".to_string();
+}
+
+fn handle_notifications_key(client: &mut RpcClient, app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    match key.code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::Normal;
+            app.status = "cancelled".to_string();
+        }
+        KeyCode::Enter => {
+            if let Some(entry) = app.notification_log.get(app.notification_selected).cloned() {
+                jump_to_notification(client, app, &entry);
+                app.input_mode = InputMode::Normal;
+            } else {
+                app.status = "no notifications".to_string();
+            }
+        }
+        KeyCode::Up => {
+            if app.notification_selected > 0 {
+                app.notification_selected -= 1;
+            }
+        }
+        KeyCode::Down => {
+            if app.notification_selected + 1 < app.notification_log.len() {
+                app.notification_selected += 1;
+            }
+        }
         _ => {}
     }
     Ok(false)
 }
 
+fn jump_to_notification(client: &mut RpcClient, app: &mut App, entry: &UnreadNotification) {
+    if let Some(index) = app.chats.iter().position(|chat| chat.id == entry.chat_id) {
+        switch_selected_chat(app, index);
+    }
+    app.focus = FocusPane::Chats;
+    app.unread.remove(&entry.chat_id);
+    app.pending_jump_guid = Some(entry.guid.clone());
+    request_history(client, app, entry.chat_id);
+    app.message_offset = 0;
+    app.message_index = 0;
+}
+
+/// Recompute `app.find_results` for the current `app.find_query`, searching
+/// loaded chats (by name/identifier) plus, when a chat is open, its loaded
+/// messages (by text/sender). Ranked descending by fuzzy score, top 20 kept.
+fn refresh_find_results(app: &mut App) {
+    let query = app.find_query.trim();
+    if query.is_empty() {
+        app.find_results.clear();
+        app.find_selected = 0;
+        return;
+    }
+    let mut scored: Vec<(i64, FindMatch)> = Vec::new();
+    for (index, chat) in app.chats.iter().enumerate() {
+        let haystack = if chat.name.is_empty() {
+            chat.identifier.clone()
+        } else {
+            format!("{} ({})", chat.name, chat.identifier)
+        };
+        if let Some((score, matched)) = fuzzy_match(query, &haystack) {
+            scored.push((
+                score,
+                FindMatch {
+                    target: FindTarget::Chat(index),
+                    display: haystack,
+                    matched,
+                },
+            ));
+        }
+    }
+    for (index, message) in app.messages.iter().enumerate() {
+        let haystack = format!("{}: {}", message.sender, message.text);
+        if let Some((score, matched)) = fuzzy_match(query, &haystack) {
+            scored.push((
+                score,
+                FindMatch {
+                    target: FindTarget::Message(index),
+                    display: haystack,
+                    matched,
+                },
+            ));
+        }
+    }
+    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    app.find_results = scored.into_iter().take(20).map(|(_, found)| found).collect();
+    app.find_selected = 0;
+}
+
+/// Subsequence fuzzy match of `query` against `haystack` (case-insensitive).
+/// Returns the score and the matched char indices into `chars` (`haystack`'s
+/// chars) for highlighting, or `None` if `query` isn't a subsequence.
+/// Consecutive matches and matches at word starts score higher than
+/// scattered ones, and a wide gap since the previous match is penalized.
+fn fuzzy_match(query: &str, haystack: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let chars: Vec<char> = haystack.chars().collect();
+    // Lowercase each `chars` entry individually (rather than
+    // `haystack.to_lowercase()` as a whole) so `lower` stays index-aligned
+    // with `chars` even for characters whose full lowercasing expands to
+    // more than one char (e.g. 'İ' -> "i̇").
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let mut matched = Vec::with_capacity(query.len());
+    let mut score: i64 = 0;
+    let mut qi = 0;
+    let mut last_match: Option<usize> = None;
+    for (i, &c) in lower.iter().enumerate() {
+        if qi >= query.len() {
+            break;
+        }
+        if c == query[qi] {
+            let mut bonus = 1;
+            if last_match == Some(i.wrapping_sub(1)) {
+                bonus += 8;
+            } else if let Some(last) = last_match {
+                let gap = i - last - 1;
+                bonus -= (gap as i64).min(6);
+            }
+            let at_word_start = i == 0 || !chars[i - 1].is_alphanumeric();
+            if at_word_start {
+                bonus += 4;
+            }
+            score += bonus;
+            matched.push(i);
+            last_match = Some(i);
+            qi += 1;
+        }
+    }
+    if qi == query.len() {
+        Some((score, matched))
+    } else {
+        None
+    }
+}
+
 fn handle_arrow_navigation(app: &mut App, code: KeyCode) {
     match app.focus {
-        FocusPane::Chats => match code {
-            KeyCode::Up => {
-                if app.selected > 0 {
-                    app.selected -= 1;
+        FocusPane::Chats => {
+            match code {
+                KeyCode::Up => {
+                    if app.selected > 0 {
+                        switch_selected_chat(app, app.selected - 1);
+                    }
                 }
-            }
-            KeyCode::Down => {
-                if app.selected + 1 < app.chats.len() {
-                    app.selected += 1;
+                KeyCode::Down => {
+                    if app.selected + 1 < app.chats.len() {
+                        switch_selected_chat(app, app.selected + 1);
+                    }
                 }
+                _ => {}
             }
-            _ => {}
-        },
+            if let Some(chat) = app.chats.get(app.selected) {
+                app.unread.remove(&chat.id);
+            }
+        }
         FocusPane::Messages => match code {
             KeyCode::Up => {
                 if app.message_index > 0 {
@@ -333,9 +866,11 @@ fn handle_enter(client: &mut RpcClient, app: &mut App) {
     match app.focus {
         FocusPane::Chats => {
             if let Some(chat) = app.chats.get(app.selected) {
-                request_history(client, app, chat.id);
+                let chat_id = chat.id;
+                request_history(client, app, chat_id);
                 app.message_offset = 0;
                 app.message_index = 0;
+                app.unread.remove(&chat_id);
             }
         }
         FocusPane::Messages => {}
@@ -354,6 +889,47 @@ fn begin_compose(app: &mut App, field: ComposeField) {
     app.status = "compose: tab switch field, shift-tab recent, enter send".to_string();
 }
 
+/// Move chat selection to `new_index`, stashing any half-typed compose
+/// fields under the chat being left and restoring a saved draft (or
+/// clearing the fields) for the chat being entered.
+fn switch_selected_chat(app: &mut App, new_index: usize) {
+    if new_index == app.selected {
+        return;
+    }
+    stash_compose_draft(app);
+    app.selected = new_index;
+    restore_compose_draft(app);
+}
+
+fn stash_compose_draft(app: &mut App) {
+    let Some(chat) = app.chats.get(app.selected) else {
+        return;
+    };
+    let chat_id = chat.id;
+    if app.compose_to.is_empty() && app.compose_body.is_empty() {
+        app.compose_drafts.remove(&chat_id);
+    } else {
+        app.compose_drafts
+            .insert(chat_id, (app.compose_to.clone(), app.compose_body.clone()));
+    }
+}
+
+fn restore_compose_draft(app: &mut App) {
+    let Some(chat) = app.chats.get(app.selected) else {
+        return;
+    };
+    match app.compose_drafts.get(&chat.id) {
+        Some((to, body)) => {
+            app.compose_to = to.clone();
+            app.compose_body = body.clone();
+        }
+        None => {
+            app.compose_to.clear();
+            app.compose_body.clear();
+        }
+    }
+}
+
 fn sender_display(app: &App, sender: &str) -> String {
     app.contacts
         .get(sender)
@@ -454,27 +1030,94 @@ fn reaction_summary(reactions: &[Reaction]) -> Option<String> {
     Some(parts.join(" "))
 }
 
-fn styled_text_lines(text: &str, base_style: Style, link_style: Style) -> Vec<Line<'static>> {
+/// Render `text` as styled lines, understanding a safe subset of Markdown
+/// (`**bold**`, `*italic*`, `` `code` ``, and fenced ``` blocks) plus URL
+/// linkification and self-mention highlighting. Fenced blocks get a
+/// distinct background and skip link/emphasis/mention processing entirely;
+/// everywhere else those features compose per inline run.
+fn styled_text_lines(
+    text: &str,
+    base_style: Style,
+    link_style: Style,
+    mention_style: Style,
+    mention_name: Option<&str>,
+) -> Vec<Line<'static>> {
     let mut finder = LinkFinder::new();
     finder.kinds(&[linkify::LinkKind::Url]);
+    let code_style = base_style.bg(Color::DarkGray).fg(Color::White);
+    let fence_style = base_style.bg(Color::Black);
     let mut lines = Vec::new();
+    let mut in_fence = false;
     for raw_line in text.split('\n') {
+        if raw_line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            lines.push(Line::from(vec![Span::styled(
+                format!("  {raw_line}  "),
+                fence_style,
+            )]));
+            continue;
+        }
+        if in_fence {
+            lines.push(Line::from(vec![Span::styled(
+                format!("  {raw_line}  "),
+                fence_style,
+            )]));
+            continue;
+        }
+
+        let (line_style, content) = match raw_line.trim_start().strip_prefix("> ") {
+            Some(rest) => (base_style.add_modifier(Modifier::ITALIC).fg(Color::Gray), rest),
+            None => (base_style, raw_line),
+        };
         let mut spans: Vec<Span<'static>> = Vec::new();
-        spans.push(Span::styled("  ", base_style));
-        let mut last = 0;
-        for link in finder.links(raw_line) {
-            let start = link.start();
-            let end = link.end();
-            if start > last {
-                spans.push(Span::styled(raw_line[last..start].to_string(), base_style));
-            }
-            spans.push(Span::styled(raw_line[start..end].to_string(), link_style));
-            last = end;
+        spans.push(Span::styled("  ", line_style));
+        if content != raw_line {
+            spans.push(Span::styled("▏ ", line_style));
         }
-        if last < raw_line.len() {
-            spans.push(Span::styled(raw_line[last..].to_string(), base_style));
+        for (run_text, emphasis, is_code) in parse_inline_markdown(content) {
+            if is_code {
+                spans.push(Span::styled(run_text, code_style));
+                continue;
+            }
+            let mut run_style = line_style;
+            if emphasis.bold {
+                run_style = run_style.add_modifier(Modifier::BOLD);
+            }
+            if emphasis.italic {
+                run_style = run_style.add_modifier(Modifier::ITALIC);
+            }
+            if emphasis.strikethrough {
+                run_style = run_style.add_modifier(Modifier::CROSSED_OUT);
+            }
+            let run_link_style = link_style.patch(run_style);
+
+            let mut ranges: Vec<(usize, usize, Style)> = finder
+                .links(&run_text)
+                .map(|link| (link.start(), link.end(), run_link_style))
+                .collect();
+            if let Some(name) = mention_name.filter(|name| !name.trim().is_empty()) {
+                for (start, end) in mention_ranges(&run_text, name) {
+                    let overlaps_link = ranges.iter().any(|(s, e, _)| start < *e && *s < end);
+                    if !overlaps_link {
+                        ranges.push((start, end, mention_style));
+                    }
+                }
+                ranges.sort_by_key(|(start, _, _)| *start);
+            }
+
+            let mut last = 0;
+            for (start, end, style) in ranges {
+                if start > last {
+                    spans.push(Span::styled(run_text[last..start].to_string(), run_style));
+                }
+                spans.push(Span::styled(run_text[start..end].to_string(), style));
+                last = end;
+            }
+            if last < run_text.len() {
+                spans.push(Span::styled(run_text[last..].to_string(), run_style));
+            }
         }
-        spans.push(Span::styled("  ", base_style));
+        spans.push(Span::styled("  ", line_style));
         lines.push(Line::from(spans));
     }
     if lines.is_empty() {
@@ -483,6 +1126,138 @@ fn styled_text_lines(text: &str, base_style: Style, link_style: Style) -> Vec<Li
     lines
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+struct Emphasis {
+    bold: bool,
+    italic: bool,
+    strikethrough: bool,
+}
+
+/// Tokenize a single line into `(text, emphasis, is_code)` runs, toggling
+/// bold on `**`, italic on `*`/`_`, strikethrough on `~~`, and code on
+/// `` ` ``. Unmatched markers are treated as literal text rather than left
+/// dangling.
+fn parse_inline_markdown(line: &str) -> Vec<(String, Emphasis, bool)> {
+    let mut runs = Vec::new();
+    let mut current = String::new();
+    let mut emphasis = Emphasis::default();
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '`' {
+            if !current.is_empty() {
+                runs.push((std::mem::take(&mut current), emphasis, false));
+            }
+            let mut code = String::new();
+            let mut closed = false;
+            for (_, c2) in chars.by_ref() {
+                if c2 == '`' {
+                    closed = true;
+                    break;
+                }
+                code.push(c2);
+            }
+            if closed {
+                runs.push((code, Emphasis::default(), true));
+            } else {
+                current.push('`');
+                current.push_str(&code);
+            }
+            continue;
+        }
+        let marker = ["~~", "**", "*", "_"].into_iter().find(|m| line[i..].starts_with(*m));
+        if let Some(marker) = marker {
+            let active = match marker {
+                "~~" => emphasis.strikethrough,
+                "**" => emphasis.bold,
+                _ => emphasis.italic,
+            };
+            if !active && !line[i + marker.len()..].contains(marker) {
+                current.push_str(marker);
+                if marker.len() == 2 {
+                    chars.next();
+                }
+                continue;
+            }
+        }
+        if c == '~' && line[i..].starts_with("~~") {
+            if !current.is_empty() {
+                runs.push((std::mem::take(&mut current), emphasis, false));
+            }
+            emphasis.strikethrough = !emphasis.strikethrough;
+            chars.next();
+            continue;
+        }
+        if c == '_' {
+            if !current.is_empty() {
+                runs.push((std::mem::take(&mut current), emphasis, false));
+            }
+            emphasis.italic = !emphasis.italic;
+            continue;
+        }
+        if c == '*' {
+            if line[i..].starts_with("**") {
+                if !current.is_empty() {
+                    runs.push((std::mem::take(&mut current), emphasis, false));
+                }
+                emphasis.bold = !emphasis.bold;
+                chars.next();
+                continue;
+            }
+            if !current.is_empty() {
+                runs.push((std::mem::take(&mut current), emphasis, false));
+            }
+            emphasis.italic = !emphasis.italic;
+            continue;
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        runs.push((current, emphasis, false));
+    }
+    runs
+}
+
+/// Find byte ranges where `name` occurs in `text` as a whole word: the
+/// character immediately before and after the match must each be either
+/// absent (a string boundary) or non-alphanumeric, so "Tom" matches in
+/// "hey Tom!" but not in "Tommy" or "@Tomás".
+fn mention_ranges(text: &str, name: &str) -> Vec<(usize, usize)> {
+    if name.is_empty() {
+        return Vec::new();
+    }
+    let mut ranges = Vec::new();
+    let mut search_from = 0;
+    while search_from <= text.len() {
+        let Some(offset) = text[search_from..].find(name) else {
+            break;
+        };
+        let start = search_from + offset;
+        let end = start + name.len();
+        let before_ok = text[..start]
+            .chars()
+            .next_back()
+            .map(|c| !c.is_alphanumeric())
+            .unwrap_or(true);
+        let after_ok = text[end..]
+            .chars()
+            .next()
+            .map(|c| !c.is_alphanumeric())
+            .unwrap_or(true);
+        if before_ok && after_ok {
+            ranges.push((start, end));
+        }
+        search_from = start + 1;
+    }
+    ranges
+}
+
+fn message_mentions_self(message: &Message, mention_name: Option<&str>) -> bool {
+    match mention_name.filter(|name| !name.trim().is_empty()) {
+        Some(name) => !mention_ranges(&message.text, name).is_empty(),
+        None => false,
+    }
+}
+
 fn handle_open_url(app: &mut App) {
     if let Some(message) = current_message(app) {
         let urls = extract_urls(&message.text);
@@ -511,6 +1286,14 @@ fn handle_reaction(app: &mut App) {
     }
 }
 
+fn handle_summarize(client: &mut RpcClient, app: &mut App) {
+    if app.messages.is_empty() {
+        app.status = "no messages loaded to summarize".to_string();
+        return;
+    }
+    request_summarize(client, app);
+}
+
 fn handle_compose_key(client: &mut RpcClient, app: &mut App, key: KeyEvent) -> io::Result<bool> {
     match key.code {
         KeyCode::Esc => {
@@ -603,11 +1386,26 @@ fn handle_input_reaction(
 }
 
 fn send_compose(client: &mut RpcClient, app: &mut App) -> bool {
-    let text = app.compose_body.trim().to_string();
+    let with_math = match substitute_inline_math(&app.compose_body) {
+        Ok(substituted) => substituted,
+        Err(err) => {
+            app.status = format!("math error: {err}");
+            return false;
+        }
+    };
+    let applied = apply_compose_transform(&with_math);
+    let text = match &applied {
+        Some((transformed, _)) => transformed.clone(),
+        None => with_math.trim().to_string(),
+    };
     if text.is_empty() {
         app.status = "message text required".to_string();
         return false;
     }
+    let sent_status = match &applied {
+        Some((_, name)) => format!("sent ({name} applied)"),
+        None => "sent".to_string(),
+    };
     let target = app.compose_to.trim().to_string();
     if target.is_empty() {
         if let Some(chat) = app.chats.get(app.selected).cloned() {
@@ -616,7 +1414,7 @@ fn send_compose(client: &mut RpcClient, app: &mut App) -> bool {
                 record_recipient(app, &chat.identifier);
             }
             app.compose_body.clear();
-            app.status = "sent".to_string();
+            app.status = sent_status;
             return true;
         }
         app.status = "no chat selected".to_string();
@@ -626,7 +1424,7 @@ fn send_compose(client: &mut RpcClient, app: &mut App) -> bool {
         request_send_to(client, app, &target, &text);
         record_recipient(app, &target);
         app.compose_body.clear();
-        app.status = "sent".to_string();
+        app.status = sent_status;
         return true;
     }
     if let Some((_, handle)) = app.contact_suggestions.first().cloned() {
@@ -634,13 +1432,317 @@ fn send_compose(client: &mut RpcClient, app: &mut App) -> bool {
         app.compose_to = handle.clone();
         record_recipient(app, &handle);
         app.compose_body.clear();
-        app.status = "sent".to_string();
+        app.status = sent_status;
         return true;
     }
     app.status = "unknown recipient; enter handle".to_string();
     false
 }
 
+/// Recognize a leading `/leet`, `/owo`, or `/mock` command in a compose
+/// body and apply the matching transform to the remainder, returning the
+/// transformed text and the command name for the status line.
+fn apply_compose_transform(body: &str) -> Option<(String, &'static str)> {
+    let trimmed = body.trim_start();
+    let commands: [(&str, &str, fn(&str) -> String); 3] = [
+        ("/leet", "leet", leetify),
+        ("/owo", "owo", owoify),
+        ("/mock", "mock", mockify),
+    ];
+    for (prefix, name, transform) in commands {
+        if let Some(rest) = trimmed.strip_prefix(prefix) {
+            return Some((transform(rest.trim_start()), name));
+        }
+    }
+    None
+}
+
+fn leetify(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'a' | 'A' => '4',
+            'e' | 'E' => '3',
+            'l' | 'L' => '1',
+            'o' | 'O' => '0',
+            't' | 'T' => '7',
+            's' | 'S' => '5',
+            other => other,
+        })
+        .collect()
+}
+
+const OWO_SUFFIXES: [&str; 4] = [" uwu", " owo", " >w<", " nya~"];
+
+fn owoify(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(chars.len() + 8);
+    for (index, &c) in chars.iter().enumerate() {
+        let replaced = match c {
+            'r' | 'l' => 'w',
+            'R' | 'L' => 'W',
+            _ => c,
+        };
+        result.push(replaced);
+        if matches!(c, 'n' | 'N') {
+            if let Some(&next) = chars.get(index + 1) {
+                if "aeiouAEIOU".contains(next) {
+                    result.push(if c.is_uppercase() { 'Y' } else { 'y' });
+                }
+            }
+        }
+    }
+    let suffix = OWO_SUFFIXES[text.len() % OWO_SUFFIXES.len()];
+    result.push_str(suffix);
+    result
+}
+
+fn mockify(text: &str) -> String {
+    let mut upper = false;
+    text.chars()
+        .map(|c| {
+            if c.is_alphabetic() {
+                let out = if upper {
+                    c.to_ascii_uppercase()
+                } else {
+                    c.to_ascii_lowercase()
+                };
+                upper = !upper;
+                out
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// A single token produced while scanning an `=( … )` expression.
+#[derive(Debug, Clone)]
+enum MathToken {
+    Number(f64),
+    Op(char),
+    /// Unary minus, e.g. the `-` in `-5` or `3 * -2`, distinguished from
+    /// binary `Op('-')` so it can bind tighter than every binary operator
+    /// except `^`.
+    Neg,
+    Func(String),
+    LParen,
+    RParen,
+}
+
+/// A `-` is unary (negation) rather than binary subtraction when it starts
+/// the expression or follows another operator/`(` — i.e. there's no left
+/// operand for it to subtract from yet.
+fn is_unary_position(tokens: &[MathToken]) -> bool {
+    matches!(
+        tokens.last(),
+        None | Some(MathToken::Op(_) | MathToken::Neg | MathToken::LParen)
+    )
+}
+
+fn tokenize_math(expr: &str) -> Result<Vec<MathToken>, String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{text}'"))?;
+            tokens.push(MathToken::Number(value));
+            continue;
+        }
+        if c.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && chars[i].is_alphanumeric() {
+                i += 1;
+            }
+            tokens.push(MathToken::Func(chars[start..i].iter().collect()));
+            continue;
+        }
+        match c {
+            '-' if is_unary_position(&tokens) => tokens.push(MathToken::Neg),
+            '+' | '-' | '*' | '/' | '^' => tokens.push(MathToken::Op(c)),
+            '(' => tokens.push(MathToken::LParen),
+            ')' => tokens.push(MathToken::RParen),
+            _ => return Err(format!("unexpected character '{c}'")),
+        }
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+fn math_precedence(op: char) -> u8 {
+    match op {
+        '+' | '-' => 1,
+        '*' | '/' => 2,
+        '^' => 3,
+        _ => 0,
+    }
+}
+
+/// Shunting-yard: convert infix tokens to RPN, respecting precedence and
+/// the right-associativity of `^`.
+fn math_to_rpn(tokens: Vec<MathToken>) -> Result<Vec<MathToken>, String> {
+    let mut output = Vec::new();
+    let mut stack: Vec<MathToken> = Vec::new();
+    for token in tokens {
+        match token {
+            MathToken::Number(_) => output.push(token),
+            MathToken::Func(_) => stack.push(token),
+            MathToken::Op(op) => {
+                while let Some(top) = stack.last() {
+                    let pop = match top {
+                        MathToken::Op(top_op) => {
+                            if op == '^' {
+                                math_precedence(*top_op) > math_precedence(op)
+                            } else {
+                                math_precedence(*top_op) >= math_precedence(op)
+                            }
+                        }
+                        MathToken::Func(_) => true,
+                        // Unary minus binds looser than `^` (so `-2^2` is
+                        // `-(2^2) = -4`, the conventional math reading) but
+                        // tighter than every other binary operator.
+                        MathToken::Neg => op != '^',
+                        _ => false,
+                    };
+                    if !pop {
+                        break;
+                    }
+                    output.push(stack.pop().unwrap());
+                }
+                stack.push(MathToken::Op(op));
+            }
+            // A prefix operator: nothing to pop yet, it binds to whatever
+            // comes next and is popped once that operand is fully reduced.
+            MathToken::Neg => stack.push(token),
+            MathToken::LParen => stack.push(token),
+            MathToken::RParen => {
+                let mut closed = false;
+                while let Some(top) = stack.pop() {
+                    if matches!(top, MathToken::LParen) {
+                        closed = true;
+                        break;
+                    }
+                    output.push(top);
+                }
+                if !closed {
+                    return Err("mismatched parentheses".to_string());
+                }
+                if let Some(MathToken::Func(_)) = stack.last() {
+                    output.push(stack.pop().unwrap());
+                }
+            }
+        }
+    }
+    while let Some(top) = stack.pop() {
+        if matches!(top, MathToken::LParen | MathToken::RParen) {
+            return Err("mismatched parentheses".to_string());
+        }
+        output.push(top);
+    }
+    Ok(output)
+}
+
+fn eval_math_rpn(rpn: &[MathToken]) -> Result<f64, String> {
+    let mut stack: Vec<f64> = Vec::new();
+    for token in rpn {
+        match token {
+            MathToken::Number(n) => stack.push(*n),
+            MathToken::Op(op) => {
+                let b = stack.pop().ok_or("missing operand")?;
+                let a = stack.pop().ok_or("missing operand")?;
+                stack.push(match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    '/' => a / b,
+                    '^' => a.powf(b),
+                    other => return Err(format!("unknown operator '{other}'")),
+                });
+            }
+            MathToken::Func(name) => {
+                let a = stack.pop().ok_or("missing operand")?;
+                stack.push(match name.as_str() {
+                    "sqrt" => a.sqrt(),
+                    "sin" => a.sin(),
+                    "cos" => a.cos(),
+                    "log" => a.log10(),
+                    other => return Err(format!("unknown function '{other}'")),
+                });
+            }
+            MathToken::Neg => {
+                let a = stack.pop().ok_or("missing operand")?;
+                stack.push(-a);
+            }
+            MathToken::LParen | MathToken::RParen => {
+                return Err("unexpected parenthesis".to_string());
+            }
+        }
+    }
+    if stack.len() != 1 {
+        return Err("incomplete expression".to_string());
+    }
+    Ok(stack[0])
+}
+
+fn evaluate_math_expression(expr: &str) -> Result<f64, String> {
+    let tokens = tokenize_math(expr)?;
+    let rpn = math_to_rpn(tokens)?;
+    eval_math_rpn(&rpn)
+}
+
+fn format_math_result(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{value}")
+    }
+}
+
+/// Replace every `=( … )` span in `text` with the numeric result of
+/// evaluating its contents. Returns an error (leaving the caller's text
+/// untouched) on the first unterminated or unparsable expression.
+fn substitute_inline_math(text: &str) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("=(") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let mut depth = 1;
+        let mut end = None;
+        for (i, c) in after.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let end = end.ok_or_else(|| "unterminated =( expression".to_string())?;
+        let value = evaluate_math_expression(&after[..end])?;
+        result.push_str(&format_math_result(value));
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
 fn record_recipient(app: &mut App, handle: &str) {
     let trimmed = handle.trim();
     if trimmed.is_empty() {
@@ -649,6 +1751,7 @@ fn record_recipient(app: &mut App, handle: &str) {
     app.recipient_history.retain(|item| item != trimmed);
     app.recipient_history.insert(0, trimmed.to_string());
     app.history_index = None;
+    save_persisted_state(app);
 }
 
 fn cycle_recipient_history(app: &mut App) {
@@ -678,10 +1781,14 @@ fn help_text() -> &'static str {
 q quit  Tab focus  Enter history  w watch  r refresh\n\
 s send (compose)  n new (compose to)  c compose\n\
 R react  o open url  PgUp/PgDn scroll  j/k scroll\n\
+/ find chats & messages  : command palette  S summarize  N notifications\n\
 \n\
 compose mode\n\
 Tab switch field  Shift-Tab recent recipient\n\
-Enter send  Esc cancel\n"
+Enter send  Esc cancel\n\
+\n\
+find / palette mode\n\
+Up/Down select  Enter jump/run  Esc cancel\n"
 }
 
 fn centered_rect(area: ratatui::layout::Rect, width_ratio: u16, height_ratio: u16) -> ratatui::layout::Rect {
@@ -709,92 +1816,156 @@ fn centered_rect(area: ratatui::layout::Rect, width_ratio: u16, height_ratio: u1
         .split(popup_layout[1])[1]
 }
 
+/// Record a just-sent request under `pending`, or surface its write failure
+/// in `app.status` if the transport couldn't take it.
+fn track_request(app: &mut App, result: io::Result<String>, pending: PendingRequest) {
+    match result {
+        Ok(id) => {
+            app.pending.insert(id, pending);
+        }
+        Err(err) => {
+            app.status = format!("send failed: {err}");
+        }
+    }
+}
+
 fn request_chats(client: &mut RpcClient, app: &mut App) {
-    let id = client.send_request("chats.list", Some(serde_json::json!({ "limit": 50 })));
-    app.pending.insert(id, PendingRequest::Chats);
+    let result = client.send_request("chats.list", Some(serde_json::json!({ "limit": 50 })));
+    track_request(app, result, PendingRequest::Chats);
     app.status = "loading chats...".to_string();
 }
 
 fn request_history(client: &mut RpcClient, app: &mut App, chat_id: i64) {
-    let id = client.send_request(
+    let result = client.send_request(
         "messages.history",
         Some(serde_json::json!({ "chat_id": chat_id, "limit": 50 })),
     );
-    app.pending.insert(id, PendingRequest::History);
+    track_request(app, result, PendingRequest::History);
     app.status = format!("loading history for chat {}", chat_id);
 }
 
 fn request_reaction(client: &mut RpcClient, app: &mut App, guid: &str, reaction: &str) {
-    let id = client.send_request(
+    let result = client.send_request(
         "reactions.send",
         Some(serde_json::json!({ "guid": guid, "reaction": reaction })),
     );
-    app.pending.insert(id, PendingRequest::Reaction);
+    track_request(app, result, PendingRequest::Reaction);
     app.status = "sending reaction...".to_string();
 }
 
+/// Approximate token count for a message: a byte/4 heuristic plus a small
+/// fixed overhead for the role/header wrapping an LLM prompt adds.
+fn approx_message_tokens(message: &Message) -> usize {
+    message.text.len() / 4 + 8
+}
+
+/// Select the most recent messages from `messages` that fit in
+/// `budget_tokens`, always keeping the most recent exchange (the last two
+/// messages) even if that alone exceeds the budget. Returns the kept
+/// messages in their original order plus how many were dropped.
+fn budget_messages(messages: &[Message], budget_tokens: usize) -> (Vec<Message>, usize) {
+    let keep_at_least = messages.len().min(2);
+    let mut total = 0;
+    let mut kept = 0;
+    for message in messages.iter().rev() {
+        let tokens = approx_message_tokens(message);
+        if kept >= keep_at_least && total + tokens > budget_tokens {
+            break;
+        }
+        total += tokens;
+        kept += 1;
+    }
+    let dropped = messages.len() - kept;
+    (messages[dropped..].to_vec(), dropped)
+}
+
+fn request_summarize(client: &mut RpcClient, app: &mut App) {
+    let (included, dropped) = budget_messages(&app.messages, app.context_tokens);
+    let payload: Vec<Value> = included
+        .iter()
+        .map(|message| {
+            serde_json::json!({
+                "sender": message.sender,
+                "text": message.text,
+                "created_at": message.created_at,
+                "is_from_me": message.is_from_me,
+            })
+        })
+        .collect();
+    let result = client.send_request(
+        "ai.summarize",
+        Some(serde_json::json!({ "messages": payload })),
+    );
+    track_request(app, result, PendingRequest::Summarize);
+    app.status = format!(
+        "summarizing {} messages ({} truncated)...",
+        included.len(),
+        dropped
+    );
+}
+
 fn request_contact_resolve(client: &mut RpcClient, app: &mut App, handles: &[String]) {
-    let id = client.send_request(
+    let result = client.send_request(
         "contacts.resolve",
         Some(serde_json::json!({ "handles": handles })),
     );
-    app.pending.insert(id, PendingRequest::ResolveContacts);
+    track_request(app, result, PendingRequest::ResolveContacts);
 }
 
 fn request_contact_search(client: &mut RpcClient, app: &mut App, query: &str) {
-    let id = client.send_request(
+    let result = client.send_request(
         "contacts.search",
         Some(serde_json::json!({ "query": query, "limit": 10 })),
     );
-    app.pending.insert(id, PendingRequest::ContactSearch);
+    track_request(app, result, PendingRequest::ContactSearch);
 }
 
 fn toggle_watch(client: &mut RpcClient, app: &mut App, chat_id: i64) {
     if app.watch_subscription.is_some() {
         if let Some(sub) = app.watch_subscription.clone() {
-            let id = client.send_request(
+            let result = client.send_request(
                 "watch.unsubscribe",
                 Some(serde_json::json!({ "subscription": sub })),
             );
-            app.pending.insert(id, PendingRequest::WatchUnsubscribe);
+            track_request(app, result, PendingRequest::WatchUnsubscribe);
             app.status = "unsubscribing...".to_string();
             app.watch_chat_id = None;
         }
         return;
     }
     app.watch_chat_id = Some(chat_id);
-    let id = client.send_request(
+    let result = client.send_request(
         "watch.subscribe",
         Some(serde_json::json!({ "chat_id": chat_id })),
     );
-    app.pending.insert(id, PendingRequest::WatchSubscribe);
+    track_request(app, result, PendingRequest::WatchSubscribe);
     app.status = "subscribing...".to_string();
 }
 
 fn request_watch_subscribe(client: &mut RpcClient, app: &mut App, chat_id: i64) {
-    let id = client.send_request(
+    let result = client.send_request(
         "watch.subscribe",
         Some(serde_json::json!({ "chat_id": chat_id })),
     );
-    app.pending.insert(id, PendingRequest::WatchSubscribe);
+    track_request(app, result, PendingRequest::WatchSubscribe);
     app.status = "subscribing...".to_string();
 }
 
 fn request_send_chat(client: &mut RpcClient, app: &mut App, chat_id: i64, text: &str) {
-    let id = client.send_request(
+    let result = client.send_request(
         "send",
         Some(serde_json::json!({ "chat_id": chat_id, "text": text })),
     );
-    app.pending.insert(id, PendingRequest::Send);
+    track_request(app, result, PendingRequest::Send(Some(chat_id)));
     app.status = "sending...".to_string();
 }
 
 fn request_send_to(client: &mut RpcClient, app: &mut App, to: &str, text: &str) {
-    let id = client.send_request(
+    let result = client.send_request(
         "send",
         Some(serde_json::json!({ "to": to, "text": text })),
     );
-    app.pending.insert(id, PendingRequest::Send);
+    track_request(app, result, PendingRequest::Send(None));
     app.status = "sending...".to_string();
 }
 
@@ -827,11 +1998,25 @@ fn handle_rpc_events(client: &mut RpcClient, app: &mut App) {
                             .unwrap_or(false);
                         if should_append {
                             app.messages.push(message.clone());
+                        } else if !message.is_from_me {
+                            *app.unread.entry(message.chat_id).or_insert(0) += 1;
+                            record_unread_notification(app, &message);
                         }
                         if !app.contacts.contains_key(&message.sender) {
                             request_contact_resolve(client, app, &[message.sender.clone()]);
                         }
-                        if app.notify && !message.is_from_me {
+                        let mentions_self =
+                            message_mentions_self(&message, app.mention_name.as_deref());
+                        if mentions_self && !message.is_from_me {
+                            // Mentions notify regardless of --notify or whether this
+                            // chat is the one currently being watched.
+                            let sender = sender_display(app, &message.sender);
+                            let _ = Notification::new()
+                                .summary(&format!("{sender} mentioned you"))
+                                .body(&message.text)
+                                .appname("imsg")
+                                .show();
+                        } else if app.notify && !message.is_from_me {
                             let sender = sender_display(app, &message.sender);
                             let _ = Notification::new()
                                 .summary(&sender)
@@ -944,6 +2129,13 @@ fn handle_response(client: &mut RpcClient, app: &mut App, pending: PendingReques
             app.messages = messages;
             app.message_index = 0;
             app.message_offset = 0;
+            if let Some(guid) = app.pending_jump_guid.take() {
+                if let Some(index) = app.messages.iter().position(|m| m.guid == guid) {
+                    app.message_index = index;
+                    app.message_offset = index;
+                    app.focus = FocusPane::Messages;
+                }
+            }
             app.status = "history loaded".to_string();
             app.contact_suggestions.clear();
             let handles: Vec<String> = app
@@ -968,7 +2160,10 @@ fn handle_response(client: &mut RpcClient, app: &mut App, pending: PendingReques
             app.watch_chat_id = None;
             app.status = "watch unsubscribed".to_string();
         }
-        PendingRequest::Send => {
+        PendingRequest::Send(chat_id) => {
+            if let Some(chat_id) = chat_id {
+                app.compose_drafts.remove(&chat_id);
+            }
             app.status = "sent".to_string();
         }
         PendingRequest::ResolveContacts => {
@@ -1019,6 +2214,15 @@ fn handle_response(client: &mut RpcClient, app: &mut App, pending: PendingReques
         PendingRequest::Reaction => {
             app.status = "reaction sent".to_string();
         }
+        PendingRequest::Summarize => {
+            let summary = result
+                .get("summary")
+                .and_then(|v| v.as_str())
+                .unwrap_or("(no summary returned)")
+                .to_string();
+            app.summary = Some(summary);
+            app.status = "summary ready".to_string();
+        }
     }
 }
 
@@ -1162,6 +2366,93 @@ mod tests {
         assert_eq!(reconnect_delay(4).as_secs(), 30);
         assert_eq!(reconnect_delay(10).as_secs(), 30);
     }
+
+    #[test]
+    fn fuzzy_match_does_not_panic_on_expanding_lowercase() {
+        // 'İ' (LATIN CAPITAL LETTER I WITH DOT ABOVE) lowercases to two chars
+        // ("i" + a combining dot above), so a naive `haystack.to_lowercase()`
+        // would desync `lower` from `chars` and index out of bounds.
+        let (_, matched) = fuzzy_match("ist", "İstanbul").unwrap();
+        assert_eq!(matched, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn mention_ranges_matches_whole_word_only() {
+        assert_eq!(mention_ranges("hey Tom!", "Tom"), vec![(4, 7)]);
+        assert!(mention_ranges("Tommy is here", "Tom").is_empty());
+        assert!(mention_ranges("ping @Tomás", "Tom").is_empty());
+    }
+
+    #[test]
+    fn mention_ranges_finds_every_occurrence() {
+        let ranges = mention_ranges("Tom, are you there Tom?", "Tom");
+        assert_eq!(ranges, vec![(0, 3), (19, 22)]);
+    }
+
+    #[test]
+    fn mention_ranges_matches_at_string_end() {
+        assert_eq!(mention_ranges("ping Tom", "Tom"), vec![(5, 8)]);
+    }
+
+    #[test]
+    fn leetify_maps_known_letters_case_insensitively() {
+        assert_eq!(leetify("Leet Speak"), "1337 5p34k");
+        assert_eq!(leetify("no matches here"), "n0 m47ch35 h3r3");
+    }
+
+    #[test]
+    fn owoify_replaces_r_and_l_and_appends_length_suffix() {
+        assert_eq!(owoify("hello"), "hewwo owo");
+        assert_eq!(owoify("Really"), "Weawwy >w<");
+    }
+
+    #[test]
+    fn owoify_inserts_y_after_n_before_a_vowel_only() {
+        assert_eq!(owoify("nice"), "nyice uwu");
+        assert_eq!(owoify("Nancy"), "NYancy owo");
+    }
+
+    #[test]
+    fn mockify_alternates_case_starting_lowercase() {
+        assert_eq!(mockify("Hello World!"), "hElLo WoRlD!");
+    }
+
+    #[test]
+    fn math_unary_minus_negates_literal_and_parens() {
+        assert_eq!(evaluate_math_expression("-5"), Ok(-5.0));
+        assert_eq!(evaluate_math_expression("(-5)"), Ok(-5.0));
+        assert_eq!(evaluate_math_expression("3 * -2"), Ok(-6.0));
+    }
+
+    #[test]
+    fn math_unary_minus_binds_looser_than_power() {
+        // Conventional reading: -2^2 is -(2^2) = -4, not (-2)^2 = 4.
+        assert_eq!(evaluate_math_expression("-2^2"), Ok(-4.0));
+    }
+
+    #[test]
+    fn math_respects_precedence_and_right_assoc_power() {
+        assert_eq!(evaluate_math_expression("2 + 3 * 4"), Ok(14.0));
+        // right-associative: 2 ^ (3 ^ 2) = 2 ^ 9 = 512, not (2 ^ 3) ^ 2 = 64
+        assert_eq!(evaluate_math_expression("2 ^ 3 ^ 2"), Ok(512.0));
+    }
+
+    #[test]
+    fn math_division_by_zero_yields_infinity() {
+        assert_eq!(evaluate_math_expression("1 / 0"), Ok(f64::INFINITY));
+        assert_eq!(format_math_result(f64::INFINITY), "inf");
+    }
+
+    #[test]
+    fn math_error_leaves_surrounding_text_untouched() {
+        assert!(evaluate_math_expression("2 + )").is_err());
+        let result = substitute_inline_math("ok =(2+) more");
+        assert!(result.is_err());
+        assert_eq!(
+            substitute_inline_math("result: =(2+3) end").unwrap(),
+            "result: 5 end"
+        );
+    }
 }
 
 fn ui(frame: &mut ratatui::Frame, app: &App) {
@@ -1190,7 +2481,26 @@ fn ui(frame: &mut ratatui::Frame, app: &App) {
                     chat.name, chat.identifier, chat.service, chat.last_message_at
                 )
             };
-            ListItem::new(Line::from(vec![Span::raw(title)]))
+            let unread_count = app.unread.get(&chat.id).copied().unwrap_or(0);
+            let title_style = if unread_count > 0 {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let mut spans = Vec::new();
+            if unread_count > 0 {
+                spans.push(Span::styled(
+                    format!("({unread_count}) "),
+                    Style::default()
+                        .fg(Color::LightYellow)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            if app.compose_drafts.contains_key(&chat.id) {
+                spans.push(Span::styled("✎ ", Style::default().fg(Color::LightGreen)));
+            }
+            spans.push(Span::styled(title, title_style));
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -1231,7 +2541,14 @@ fn ui(frame: &mut ratatui::Frame, app: &App) {
             )]));
         }
         let link_style = base_style.add_modifier(Modifier::UNDERLINED).fg(Color::LightBlue);
-        let mut text_lines = styled_text_lines(&message.text, base_style, link_style);
+        let mention_style = base_style.add_modifier(Modifier::BOLD).fg(Color::LightYellow);
+        let mut text_lines = styled_text_lines(
+            &message.text,
+            base_style,
+            link_style,
+            mention_style,
+            app.mention_name.as_deref(),
+        );
         message_lines.append(&mut text_lines);
         if let Some(summary) = reaction_summary(&message.reactions) {
             message_lines.push(Line::from(vec![Span::styled(
@@ -1301,6 +2618,134 @@ fn ui(frame: &mut ratatui::Frame, app: &App) {
             .wrap(ratatui::widgets::Wrap { trim: true });
         frame.render_widget(help, area);
     }
+
+    if matches!(app.input_mode, InputMode::Find) {
+        let area = centered_rect(frame.size(), 70, 60);
+        frame.render_widget(Clear, area);
+        let popup = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+            .split(area);
+
+        let query = Paragraph::new(app.find_query.as_str())
+            .block(Block::default().title("Find").borders(Borders::ALL));
+        frame.render_widget(query, popup[0]);
+
+        let results: Vec<ListItem> = app
+            .find_results
+            .iter()
+            .map(|found| ListItem::new(find_result_line(found)))
+            .collect();
+        let results_list = List::new(results)
+            .block(Block::default().title("Results").borders(Borders::ALL))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol("➤ ");
+        let mut state = ratatui::widgets::ListState::default();
+        if !app.find_results.is_empty() {
+            state.select(Some(app.find_selected));
+        }
+        frame.render_stateful_widget(results_list, popup[1], &mut state);
+    }
+
+    if matches!(app.input_mode, InputMode::Palette) {
+        let area = centered_rect(frame.size(), 70, 60);
+        frame.render_widget(Clear, area);
+        let popup = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+            .split(area);
+
+        let query = Paragraph::new(app.palette_query.as_str())
+            .block(Block::default().title("Command").borders(Borders::ALL));
+        frame.render_widget(query, popup[0]);
+
+        let results: Vec<ListItem> = app
+            .palette_results
+            .iter()
+            .map(|&index| {
+                let entry = &COMMANDS[index];
+                ListItem::new(Line::from(vec![
+                    Span::raw(entry.name),
+                    Span::styled(
+                        format!("  [{}]", entry.binding),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                ]))
+            })
+            .collect();
+        let results_list = List::new(results)
+            .block(Block::default().title("Commands").borders(Borders::ALL))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol("➤ ");
+        let mut state = ratatui::widgets::ListState::default();
+        if !app.palette_results.is_empty() {
+            state.select(Some(app.palette_selected));
+        }
+        frame.render_stateful_widget(results_list, popup[1], &mut state);
+    }
+
+    if let Some(summary) = &app.summary {
+        let area = centered_rect(frame.size(), 70, 60);
+        frame.render_widget(Clear, area);
+        let popup = Paragraph::new(summary.as_str())
+            .block(
+                Block::default()
+                    .title("Thread summary (Esc to close)")
+                    .borders(Borders::ALL),
+            )
+            .wrap(ratatui::widgets::Wrap { trim: true });
+        frame.render_widget(popup, area);
+    }
+
+    if matches!(app.input_mode, InputMode::Notifications) {
+        let area = centered_rect(frame.size(), 70, 60);
+        frame.render_widget(Clear, area);
+        let entries: Vec<ListItem> = app
+            .notification_log
+            .iter()
+            .map(|entry| {
+                ListItem::new(Line::from(vec![Span::raw(format!(
+                    "{} {}: {}",
+                    entry.created_at, entry.sender, entry.snippet
+                ))]))
+            })
+            .collect();
+        let list = List::new(entries)
+            .block(
+                Block::default()
+                    .title("Notifications (Enter jump, Esc close)")
+                    .borders(Borders::ALL),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol("➤ ");
+        let mut state = ratatui::widgets::ListState::default();
+        if !app.notification_log.is_empty() {
+            state.select(Some(app.notification_selected));
+        }
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+fn find_result_line(found: &FindMatch) -> Line<'static> {
+    let matched: std::collections::HashSet<usize> = found.matched.iter().copied().collect();
+    let spans: Vec<Span<'static>> = found
+        .display
+        .chars()
+        .enumerate()
+        .map(|(index, ch)| {
+            if matched.contains(&index) {
+                Span::styled(
+                    ch.to_string(),
+                    Style::default()
+                        .fg(Color::LightYellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                Span::raw(ch.to_string())
+            }
+        })
+        .collect();
+    Line::from(spans)
 }
 
 fn app_state_list(app: &App) -> ratatui::widgets::ListState {
@@ -1,10 +1,17 @@
-use serde_json::Value;
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use serde_json::{value::RawValue, Value};
 use std::{
+    collections::HashMap,
     io::{self, BufRead, BufReader, Write},
     net::TcpStream,
     process::{Child, Command, Stdio},
-    sync::mpsc::{self, Receiver, Sender},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
     thread,
+    time::Duration,
 };
 
 pub enum RpcEvent {
@@ -14,9 +21,25 @@ pub enum RpcEvent {
     Closed { message: String },
 }
 
+/// Request ids are short-lived, high-churn string keys (inserted on send,
+/// removed on the matching response), so the pending-call map uses `FxHashMap`
+/// (`BuildHasherDefault<FxHasher>`) instead of the default SipHash — this map
+/// doesn't need DoS-resistant hashing, only speed.
+type PendingMap = Arc<Mutex<FxHashMap<String, Sender<Result<Value, Value>>>>>;
+type SubscriptionMap = Arc<Mutex<HashMap<String, Sender<Value>>>>;
+
+pub type SubscriptionId = String;
+
+struct Outgoing {
+    line: String,
+    ack: Sender<io::Result<()>>,
+}
+
 pub struct RpcClient {
-    sender: Sender<String>,
+    sender: Sender<Outgoing>,
     receiver: Receiver<RpcEvent>,
+    pending: PendingMap,
+    subscriptions: SubscriptionMap,
     _child: Option<Child>,
 }
 
@@ -35,10 +58,12 @@ impl RpcClient {
         let stdout = child.stdout.take().ok_or_else(|| {
             io::Error::new(io::ErrorKind::Other, "failed to open stdout")
         })?;
-        let (sender, receiver) = connect_with_io(stdin, stdout);
+        let (sender, receiver, pending, subscriptions) = connect_with_io(stdin, stdout);
         Ok(Self {
             sender,
             receiver,
+            pending,
+            subscriptions,
             _child: Some(child),
         })
     }
@@ -46,105 +71,363 @@ impl RpcClient {
     pub fn connect_tcp(host: &str, port: u16) -> io::Result<Self> {
         let stream = TcpStream::connect((host, port))?;
         let write_stream = stream.try_clone()?;
-        let (sender, receiver) = connect_with_io(write_stream, stream);
+        let (sender, receiver, pending, subscriptions) = connect_with_io(write_stream, stream);
         Ok(Self {
             sender,
             receiver,
+            pending,
+            subscriptions,
             _child: None,
         })
     }
 
-    pub fn send_request(&mut self, method: &str, params: Option<Value>) -> String {
+    /// Attach to an already-running daemon over a local IPC endpoint: a Unix
+    /// domain socket path on Unix, or a `\\.\pipe\...` name on Windows.
+    #[cfg(unix)]
+    pub fn connect_ipc(path: &str) -> io::Result<Self> {
+        use std::os::unix::net::UnixStream;
+        let stream = UnixStream::connect(path)?;
+        let write_stream = stream.try_clone()?;
+        let (sender, receiver, pending, subscriptions) = connect_with_io(write_stream, stream);
+        Ok(Self {
+            sender,
+            receiver,
+            pending,
+            subscriptions,
+            _child: None,
+        })
+    }
+
+    #[cfg(windows)]
+    pub fn connect_ipc(path: &str) -> io::Result<Self> {
+        let pipe = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+        let write_pipe = pipe.try_clone()?;
+        let (sender, receiver, pending, subscriptions) = connect_with_io(write_pipe, pipe);
+        Ok(Self {
+            sender,
+            receiver,
+            pending,
+            subscriptions,
+            _child: None,
+        })
+    }
+
+    pub fn send_request(&mut self, method: &str, params: Option<Value>) -> io::Result<String> {
         let id = next_id();
-        let mut payload = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "method": method,
-        });
-        if let Some(params) = params {
-            if let Some(obj) = payload.as_object_mut() {
-                obj.insert("params".to_string(), params);
+        self.write_line(request_line(&id, method, params))?;
+        Ok(id)
+    }
+
+    /// Serialize `calls` as a single JSON-RPC batch (one array per line) and
+    /// return the id assigned to each call, in order.
+    pub fn send_batch(&mut self, calls: &[(&str, Option<Value>)]) -> io::Result<Vec<String>> {
+        let ids: Vec<String> = calls.iter().map(|_| next_id()).collect();
+        let line = batch_line(&ids, calls);
+        self.write_line(line)?;
+        Ok(ids)
+    }
+
+    /// Like `send_batch`, but blocks until every call in the batch has a
+    /// matching `Response`/`Error` (or the connection closes).
+    pub fn call_batch(&self, calls: &[(&str, Option<Value>)]) -> io::Result<Vec<Result<Value, Value>>> {
+        let ids: Vec<String> = calls.iter().map(|_| next_id()).collect();
+        let mut waiters = Vec::with_capacity(ids.len());
+        {
+            let mut pending = self.pending.lock().unwrap();
+            for id in &ids {
+                let (tx, rx) = mpsc::channel::<Result<Value, Value>>();
+                pending.insert(id.clone(), tx);
+                waiters.push(rx);
+            }
+        }
+        let line = batch_line(&ids, calls);
+        if let Err(err) = self.write_line(line) {
+            let mut pending = self.pending.lock().unwrap();
+            for id in &ids {
+                pending.remove(id);
+            }
+            return Err(err);
+        }
+        let results = waiters
+            .into_iter()
+            .map(|rx| {
+                rx.recv()
+                    .unwrap_or_else(|_| Err(serde_json::json!({ "message": "rpc connection closed" })))
+            })
+            .collect();
+        Ok(results)
+    }
+
+    /// Send `method` and block until a matching `Response`/`Error` arrives on the
+    /// reader thread, or the connection closes.
+    pub fn call(&self, method: &str, params: Option<Value>) -> io::Result<Result<Value, Value>> {
+        match self.call_timeout(method, params, None)? {
+            Ok(result) => Ok(result),
+            Err(_timed_out) => Ok(Err(serde_json::json!({ "message": "rpc call timed out" }))),
+        }
+    }
+
+    /// Like `call`, but gives up and returns `Err(())` if no reply arrives within `timeout`.
+    pub fn call_timeout(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        timeout: Option<Duration>,
+    ) -> io::Result<Result<Result<Value, Value>, ()>> {
+        let id = next_id();
+        let (tx, rx) = mpsc::channel::<Result<Value, Value>>();
+        self.pending.lock().unwrap().insert(id.clone(), tx);
+        if let Err(err) = self.write_line(request_line(&id, method, params)) {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(err);
+        }
+
+        let outcome = match timeout {
+            Some(timeout) => rx.recv_timeout(timeout),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match outcome {
+            Ok(result) => Ok(Ok(result)),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                Ok(Err(()))
             }
         }
-        let line = payload.to_string();
-        let _ = self.sender.send(line);
-        id
+    }
+
+    /// Enqueue `line` on the writer thread and block for its write/flush result,
+    /// so a broken pipe surfaces at the call site instead of only as a later
+    /// `RpcEvent::Closed`.
+    fn write_line(&self, line: String) -> io::Result<()> {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        if self.sender.send(Outgoing { line, ack: ack_tx }).is_err() {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "rpc writer thread gone"));
+        }
+        ack_rx
+            .recv()
+            .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::BrokenPipe, "rpc writer thread gone")))
     }
 
     pub fn events(&self) -> &Receiver<RpcEvent> {
         &self.receiver
     }
+
+    /// Issue a subscribe-style call and register a dedicated channel for the
+    /// subscription id the server hands back, so notifications carrying that
+    /// id are routed away from the general event stream.
+    pub fn subscribe(
+        &self,
+        method: &str,
+        params: Option<Value>,
+    ) -> io::Result<(SubscriptionId, Receiver<Value>)> {
+        let result = self
+            .call(method, params)?
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+        let sub_id = result
+            .get("subscription")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "response missing subscription id")
+            })?
+            .to_string();
+        let (tx, rx) = mpsc::channel();
+        self.subscriptions.lock().unwrap().insert(sub_id.clone(), tx);
+        Ok((sub_id, rx))
+    }
+
+    /// Stop routing notifications for `id` to its subscription channel and
+    /// send the matching unsubscribe RPC.
+    pub fn unsubscribe(&mut self, method: &str, id: &str) -> io::Result<()> {
+        self.subscriptions.lock().unwrap().remove(id);
+        self.send_request(method, Some(serde_json::json!({ "subscription": id })))?;
+        Ok(())
+    }
+}
+
+fn request_line(id: &str, method: &str, params: Option<Value>) -> String {
+    request_value(id, method, params).to_string()
+}
+
+fn request_value(id: &str, method: &str, params: Option<Value>) -> Value {
+    let mut payload = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+    });
+    if let Some(params) = params {
+        if let Some(obj) = payload.as_object_mut() {
+            obj.insert("params".to_string(), params);
+        }
+    }
+    payload
+}
+
+fn batch_line(ids: &[String], calls: &[(&str, Option<Value>)]) -> String {
+    let batch: Vec<Value> = ids
+        .iter()
+        .zip(calls.iter())
+        .map(|(id, (method, params))| request_value(id, method, params.clone()))
+        .collect();
+    Value::Array(batch).to_string()
 }
 
 fn connect_with_io<W: Write + Send + 'static, R: io::Read + Send + 'static>(
     writer: W,
     reader: R,
-) -> (Sender<String>, Receiver<RpcEvent>) {
-    let (tx, rx) = mpsc::channel::<String>();
+) -> (Sender<Outgoing>, Receiver<RpcEvent>, PendingMap, SubscriptionMap) {
+    let (tx, rx) = mpsc::channel::<Outgoing>();
     let (event_tx, event_rx) = mpsc::channel::<RpcEvent>();
+    let pending: PendingMap = Arc::new(Mutex::new(FxHashMap::default()));
+    let subscriptions: SubscriptionMap = Arc::new(Mutex::new(HashMap::new()));
 
     thread::spawn(move || writer_thread(writer, rx));
-    thread::spawn(move || reader_thread(reader, event_tx));
+    let reader_pending = pending.clone();
+    let reader_subscriptions = subscriptions.clone();
+    thread::spawn(move || reader_thread(reader, event_tx, reader_pending, reader_subscriptions));
 
-    (tx, event_rx)
+    (tx, event_rx, pending, subscriptions)
 }
 
-fn writer_thread<W: Write>(mut writer: W, rx: Receiver<String>) {
-    for line in rx {
-        if writeln!(writer, "{line}").is_err() {
+fn writer_thread<W: Write>(mut writer: W, rx: Receiver<Outgoing>) {
+    for outgoing in rx {
+        let result = writeln!(writer, "{}", outgoing.line).and_then(|_| writer.flush());
+        let failed = result.is_err();
+        let _ = outgoing.ack.send(result);
+        if failed {
             break;
         }
-        let _ = writer.flush();
     }
 }
 
-fn reader_thread<R: io::Read>(reader: R, event_tx: Sender<RpcEvent>) {
+/// A JSON-RPC id, which the spec allows to be either a string or a number.
+/// We normalize both to a string so the pending-call map has one key shape.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawId {
+    Str(String),
+    Num(i64),
+}
+
+impl RawId {
+    fn into_key(self) -> String {
+        match self {
+            RawId::Str(s) => s,
+            RawId::Num(n) => n.to_string(),
+        }
+    }
+}
+
+/// A lightly-parsed JSON-RPC frame: `result`/`error`/`params` stay as
+/// `RawValue` slices into the original line so the payload isn't cloned
+/// into an owned `Value` until a consumer actually needs it.
+#[derive(Deserialize)]
+struct Frame<'a> {
+    id: Option<RawId>,
+    method: Option<&'a str>,
+    #[serde(borrow)]
+    params: Option<&'a RawValue>,
+    #[serde(borrow)]
+    result: Option<&'a RawValue>,
+    #[serde(borrow)]
+    error: Option<&'a RawValue>,
+}
+
+fn raw_to_value(raw: &RawValue) -> Value {
+    serde_json::from_str(raw.get()).unwrap_or(Value::Null)
+}
+
+fn reader_thread<R: io::Read>(
+    reader: R,
+    event_tx: Sender<RpcEvent>,
+    pending: PendingMap,
+    subscriptions: SubscriptionMap,
+) {
     let buffered = BufReader::new(reader);
     for line in buffered.lines().flatten() {
-        if line.trim().is_empty() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
             continue;
         }
-        match serde_json::from_str::<Value>(&line) {
-            Ok(value) => {
-                if let Some(method) = value.get("method").and_then(|v| v.as_str()) {
-                    if let Some(params) = value.get("params") {
-                        let _ = event_tx.send(RpcEvent::Notification {
-                            method: method.to_string(),
-                            params: params.clone(),
-                        });
-                        continue;
+        let parsed = if trimmed.starts_with('[') {
+            serde_json::from_str::<Vec<&RawValue>>(trimmed).map(|batch| {
+                for element in batch {
+                    if let Ok(frame) = serde_json::from_str::<Frame>(element.get()) {
+                        dispatch_frame(frame, &event_tx, &pending, &subscriptions);
                     }
                 }
-                if let Some(id) = value.get("id").and_then(|v| v.as_str()) {
-                    if let Some(result) = value.get("result") {
-                        let _ = event_tx.send(RpcEvent::Response {
-                            id: id.to_string(),
-                            result: result.clone(),
-                        });
-                        continue;
-                    }
-                    if let Some(error) = value.get("error") {
-                        let _ = event_tx.send(RpcEvent::Error {
-                            id: Some(id.to_string()),
-                            error: error.clone(),
-                        });
-                        continue;
-                    }
-                }
-            }
-            Err(err) => {
-                let _ = event_tx.send(RpcEvent::Closed {
-                    message: format!("json parse error: {err}"),
-                });
-                break;
-            }
+            })
+        } else {
+            serde_json::from_str::<Frame>(trimmed)
+                .map(|frame| dispatch_frame(frame, &event_tx, &pending, &subscriptions))
+        };
+        if let Err(err) = parsed {
+            let _ = event_tx.send(RpcEvent::Closed {
+                message: format!("json parse error: {err}"),
+            });
+            break;
         }
     }
+    fail_all_pending(&pending);
+    subscriptions.lock().unwrap().clear();
     let _ = event_tx.send(RpcEvent::Closed {
         message: "rpc stream closed".to_string(),
     });
 }
 
+/// Route a single decoded JSON-RPC frame (whether it arrived on its own
+/// line or as one element of a batch array) to its subscription channel,
+/// pending waiter, or the general event stream.
+fn dispatch_frame(
+    frame: Frame,
+    event_tx: &Sender<RpcEvent>,
+    pending: &PendingMap,
+    subscriptions: &SubscriptionMap,
+) {
+    if let Some(method) = frame.method {
+        if let Some(params) = frame.params {
+            let params = raw_to_value(params);
+            let sub_id = params.get("subscription").and_then(|v| v.as_str());
+            if let Some(sub_id) = sub_id {
+                if let Some(sender) = subscriptions.lock().unwrap().get(sub_id) {
+                    let _ = sender.send(params.clone());
+                    return;
+                }
+            }
+            let _ = event_tx.send(RpcEvent::Notification {
+                method: method.to_string(),
+                params,
+            });
+            return;
+        }
+    }
+    if let Some(id) = frame.id.map(RawId::into_key) {
+        if let Some(result) = frame.result {
+            let result = raw_to_value(result);
+            if let Some(waiter) = pending.lock().unwrap().remove(&id) {
+                let _ = waiter.send(Ok(result));
+            } else {
+                let _ = event_tx.send(RpcEvent::Response { id, result });
+            }
+            return;
+        }
+        if let Some(error) = frame.error {
+            let error = raw_to_value(error);
+            if let Some(waiter) = pending.lock().unwrap().remove(&id) {
+                let _ = waiter.send(Err(error));
+            } else {
+                let _ = event_tx.send(RpcEvent::Error { id: Some(id), error });
+            }
+        }
+    }
+}
+
+fn fail_all_pending(pending: &PendingMap) {
+    let mut pending = pending.lock().unwrap();
+    for (_, waiter) in pending.drain() {
+        let _ = waiter.send(Err(serde_json::json!({ "message": "rpc connection closed" })));
+    }
+}
+
 fn next_id() -> String {
     use std::sync::atomic::{AtomicU64, Ordering};
     static COUNTER: AtomicU64 = AtomicU64::new(0);